@@ -2,7 +2,7 @@ extern crate discord_bots_org;
 extern crate reqwest;
 
 use discord_bots_org::ReqwestSyncClient as ApiClient;
-use reqwest::Client as ReqwestClient;
+use reqwest::blocking::Client as ReqwestClient;
 use std::sync::Arc;
 
 fn main() {
@@ -0,0 +1,109 @@
+//! Loading of the proxy's TOML config file.
+
+use discord_bots_org::route::Route;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The contents of the config file.
+#[derive(Debug, Deserialize)]
+struct File {
+    /// The API tokens of bots, keyed by bot ID.
+    #[serde(default)]
+    bots: HashMap<String, String>,
+    /// The directory to keep cached responses in, if any.
+    cache_dir: Option<PathBuf>,
+    /// The address to listen on.
+    listen: Option<String>,
+    /// The API token for routes which aren't for a bot, such as `/weekend`.
+    token: Option<String>,
+}
+
+/// The proxy's config.
+#[derive(Debug)]
+pub struct Config {
+    /// The API tokens of bots by bot ID.
+    pub bots: HashMap<u64, String>,
+    /// The directory to keep cached responses in, if any.
+    pub cache_dir: Option<PathBuf>,
+    /// The address to listen on, defaulting to `127.0.0.1:5880`.
+    pub listen: String,
+    /// The API token for routes which aren't for a bot.
+    pub token: Option<String>,
+}
+
+impl Config {
+    /// Loads the config from a TOML file.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|why| format!("reading {}: {}", path.display(), why))?;
+        let file = toml::from_str::<File>(&contents)
+            .map_err(|why| format!("parsing {}: {}", path.display(), why))?;
+
+        let mut bots = HashMap::new();
+
+        for (id, token) in file.bots {
+            let id = id.parse::<u64>().map_err(|_| {
+                format!("parsing {}: invalid bot ID `{}`", path.display(), id)
+            })?;
+
+            bots.insert(id, token);
+        }
+
+        Ok(Self {
+            bots,
+            cache_dir: file.cache_dir,
+            listen: file.listen.unwrap_or_else(|| "127.0.0.1:5880".to_owned()),
+            token: file.token.filter(|token| !token.is_empty()),
+        })
+    }
+
+    /// The token to authorize a request to a route with.
+    ///
+    /// Routes for a bot use that bot's token, and other routes use the
+    /// top-level token.
+    pub fn token(&self, route: &Route) -> Option<&str> {
+        let bot_id = match route {
+            Route::GetBotVoteCheck { bot_id, .. } => bot_id,
+            Route::GetBotVotes { id } | Route::PostBotStats { id } => id,
+            _ => return self.token.as_deref(),
+        };
+
+        self.bots.get(bot_id).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use discord_bots_org::route::Route;
+    use std::collections::HashMap;
+    use super::Config;
+
+    #[test]
+    fn test_token() {
+        let mut bots = HashMap::new();
+        bots.insert(1, "one".to_owned());
+        let mut config = Config {
+            bots,
+            cache_dir: None,
+            listen: "127.0.0.1:5880".to_owned(),
+            token: Some("top".to_owned()),
+        };
+
+        let check = Route::GetBotVoteCheck { bot_id: 1, user_id: 2 };
+        assert_eq!(config.token(&check), Some("one"));
+        assert_eq!(config.token(&Route::PostBotStats { id: 1 }), Some("one"));
+        assert_eq!(config.token(&Route::GetWeekend), Some("top"));
+
+        // Bots without a token never fall back to the top-level token.
+        assert_eq!(config.token(&Route::GetBotVotes { id: 2 }), None);
+
+        config.token = None;
+        assert_eq!(config.token(&Route::GetWeekend), None);
+        assert_eq!(config.token(&Route::GetBotVotes { id: 1 }), Some("one"));
+    }
+}
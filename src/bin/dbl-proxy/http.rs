@@ -0,0 +1,148 @@
+//! Minimal HTTP/1.1 parsing and writing for the proxy's connections.
+//!
+//! Every connection serves a single request, and is closed after the
+//! response.
+
+use discord_bots_org::bridge::operation::Response;
+use std::{
+    io::{self, BufRead, BufReader, ErrorKind, Read, Write},
+    net::TcpStream,
+};
+
+/// The largest request body accepted, in bytes.
+const MAX_BODY: usize = 1 << 20;
+
+/// The largest request line and headers accepted, in bytes.
+const MAX_HEAD: usize = 8 << 10;
+
+/// The response headers passed through from the API.
+const PASSED_HEADERS: &[&str] = &["etag", "last-modified", "retry-after"];
+
+/// A request received from a bot process.
+#[derive(Debug)]
+pub struct Incoming {
+    /// The body of the request.
+    pub body: Vec<u8>,
+    /// The method of the request, such as `GET`.
+    pub method: String,
+    /// The path and query of the request, such as `/bots/1/check?userId=2`.
+    pub target: String,
+}
+
+/// Reads a request from a connection.
+///
+/// Returns `None` if the connection was closed before sending a request.
+///
+/// # Errors
+///
+/// Returns an error of kind [`ErrorKind::InvalidData`] if the request is
+/// malformed, too large, or has a body with a transfer encoding, which isn't
+/// supported.
+pub fn read_request(stream: impl Read) -> io::Result<Option<Incoming>> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    let mut remaining = MAX_HEAD;
+
+    if read_line(&mut reader, &mut line, &mut remaining)? == 0 {
+        return Ok(None);
+    }
+
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_owned(), target.to_owned()),
+        _ => return Err(invalid("malformed request line")),
+    };
+    let mut length = 0;
+
+    loop {
+        line.clear();
+        read_line(&mut reader, &mut line, &mut remaining)?;
+
+        let (name, value) = match line.trim_end().split_once(':') {
+            Some((name, value)) => (name, value.trim()),
+            None => break,
+        };
+
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value
+                .parse()
+                .map_err(|_| invalid("invalid content length"))?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(invalid("transfer encodings are not supported"));
+        }
+    }
+
+    if length > MAX_BODY {
+        return Err(invalid("request body is too large"));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Some(Incoming {
+        body,
+        method,
+        target,
+    }))
+}
+
+/// Writes a response to a connection.
+pub fn write_response(
+    mut stream: TcpStream,
+    response: &Response,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len(),
+    )?;
+
+    for name in PASSED_HEADERS {
+        if let Some(value) = response.header(name) {
+            write!(stream, "{}: {}\r\n", name, value)?;
+        }
+    }
+
+    stream.write_all(b"\r\n")?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+/// Reads a line of the request's head, failing once more than `remaining`
+/// bytes have been read without the head ending.
+fn read_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    remaining: &mut usize,
+) -> io::Result<usize> {
+    let read = reader.by_ref().take(*remaining as u64).read_line(line)?;
+    *remaining -= read;
+
+    if *remaining == 0 && !line.ends_with('\n') {
+        return Err(invalid("request head is too large"));
+    }
+
+    Ok(read)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        502 => "Bad Gateway",
+        _ => "",
+    }
+}
@@ -0,0 +1,326 @@
+//! A caching reverse proxy for the Discord Bot List API, letting many bot
+//! processes share one rate limiter and response cache.
+//!
+//! The proxy serves the same routes as the API, such as `GET /bots/{id}`,
+//! optionally prefixed with `/api`. Bot processes can use it by creating a
+//! client with [`Client::with_base`] and the proxy's URL.
+//!
+//! Requests to routes which require authorization are sent with the token of
+//! the route's bot from the config file, so bot processes never hold the real
+//! tokens. Any `Authorization` header they send is ignored.
+//!
+//! The proxy doesn't authenticate bot processes, so any local process which
+//! can connect to `listen` can post stats or read votes as any bot in the
+//! config file. Only listen on an address which untrusted processes can't
+//! reach, such as the loopback address of a host running only your bots.
+//!
+//! Each forwarded request is logged to stderr by its route's template rather
+//! than its path, such as `GET /bots/{id}/check 200`, so logs don't contain
+//! user IDs and lines for the same endpoint can be grouped.
+//!
+//! The config file is TOML, such as:
+//!
+//! ```toml
+//! listen = "127.0.0.1:5880"
+//! cache_dir = "/var/cache/dbl-proxy"
+//! # Used for routes which aren't for a bot, such as `/weekend`.
+//! token = "..."
+//!
+//! [bots]
+//! 270198738570444801 = "..."
+//! ```
+//!
+//! [`Client::with_base`]:
+//! ../discord_bots_org/bridge/struct.Client.html#method.with_base
+
+mod config;
+mod http;
+
+use clap::Parser;
+use discord_bots_org::{
+    bridge::{
+        cache::{Cache, CachedBackend},
+        operation::{Method, Request, Response},
+        ratelimit::{RateLimited, RateLimiter},
+        Backend,
+    },
+    route::Route,
+};
+use reqwest::blocking::Client as ReqwestClient;
+use std::{
+    error::Error,
+    io::{self, ErrorKind},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    process,
+    thread,
+    time::Duration,
+};
+use self::{config::Config, http::Incoming};
+
+/// The backend which requests are forwarded to the API with.
+type Upstream = CachedBackend<RateLimited<ReqwestClient>>;
+
+/// The number of connections served at once. Further connections wait to be
+/// accepted until one of these is closed.
+const WORKERS: usize = 32;
+
+/// Caching reverse proxy for the Discord Bot List API.
+#[derive(Debug, Parser)]
+#[command(name = "dbl-proxy", version)]
+struct Cli {
+    /// The path to the TOML config file.
+    config: PathBuf,
+    /// The address to listen on, overriding `listen` in the config file.
+    #[arg(long)]
+    listen: Option<String>,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    if let Err(why) = run(cli) {
+        eprintln!("error: {}", why);
+
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let config = Config::load(&cli.config)?;
+    let mut cache = Cache::new();
+
+    if let Some(dir) = &config.cache_dir {
+        cache.dir(dir);
+    }
+
+    let limited = RateLimited::new(ReqwestClient::new(), RateLimiter::new());
+    let upstream = CachedBackend::new(limited, cache);
+    let listen = cli.listen.as_deref().unwrap_or(&config.listen);
+    let listener = TcpListener::bind(listen)
+        .map_err(|why| format!("listening on {}: {}", listen, why))?;
+
+    eprintln!("listening on {}", listener.local_addr()?);
+
+    thread::scope(|scope| {
+        for _ in 0..WORKERS {
+            scope.spawn(|| accept(&listener, &config, &upstream));
+        }
+    });
+
+    Ok(())
+}
+
+/// Serves connections from the listener, one at a time.
+fn accept(listener: &TcpListener, config: &Config, upstream: &Upstream) {
+    loop {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(why) => {
+                eprintln!("error: accepting connection: {}", why);
+
+                continue;
+            },
+        };
+
+        if let Err(why) = serve(stream, config, upstream) {
+            eprintln!("error: serving connection: {}", why);
+        }
+    }
+}
+
+fn serve(
+    stream: TcpStream,
+    config: &Config,
+    upstream: &Upstream,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+
+    let response = match http::read_request(&stream) {
+        Ok(Some(incoming)) => forward(&incoming, config, upstream)
+            .unwrap_or_else(|(status, message)| error(status, &message)),
+        Ok(None) => return Ok(()),
+        Err(why) if why.kind() == ErrorKind::InvalidData => {
+            error(400, &why.to_string())
+        },
+        Err(why) => return Err(why),
+    };
+
+    http::write_response(stream, &response)
+}
+
+/// Creates a response with a JSON body describing an error.
+fn error(status: u16, message: &str) -> Response {
+    Response {
+        body: serde_json::json!({ "error": message }).to_string().into_bytes(),
+        headers: Vec::new(),
+        status,
+    }
+}
+
+fn forward(
+    incoming: &Incoming,
+    config: &Config,
+    upstream: &impl Backend,
+) -> Result<Response, (u16, String)> {
+    let method = match incoming.method.as_str() {
+        "GET" => Method::Get,
+        "POST" => Method::Post,
+        _ => return Err((405, "method not allowed".to_owned())),
+    };
+    let target = incoming.target.as_str();
+    let target = target.strip_prefix("/api").unwrap_or(target);
+    let route = Route::parse(method, target)
+        .ok_or_else(|| (404, "unknown route".to_owned()))?;
+    let mut request =
+        Request::new(route).map_err(|why| (400, why.to_string()))?;
+
+    if request.route.requires_auth() {
+        let token = config.token(&request.route).ok_or_else(|| {
+            (403, "no token is configured for this route".to_owned())
+        })?;
+
+        request = request.auth(token);
+    }
+
+    if method == Method::Post {
+        request.body = Some(incoming.body.clone());
+        request.headers.push(("Content-Type", "application/json".to_owned()));
+    }
+
+    let result = upstream.execute(&request);
+    let outcome = match &result {
+        Ok(response) => response.status.to_string(),
+        Err(why) => format!("error: {}", why),
+    };
+
+    eprintln!(
+        "{} {} {}",
+        method.as_str(),
+        request.route.template(),
+        outcome,
+    );
+
+    result.map_err(|why| (502, why.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use discord_bots_org::{
+        bridge::{
+            operation::{Request, Response},
+            Backend,
+        },
+        Result,
+    };
+    use std::{collections::HashMap, sync::Mutex};
+    use super::{config::Config, forward, http::read_request};
+
+    #[derive(Debug, Default)]
+    struct Fake {
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl Backend for Fake {
+        fn execute(&self, request: &Request) -> Result<Response> {
+            self.requests.lock().unwrap().push(request.clone());
+
+            Ok(Response {
+                body: b"{}".to_vec(),
+                headers: Vec::new(),
+                status: 200,
+            })
+        }
+    }
+
+    fn config() -> Config {
+        let mut bots = HashMap::new();
+        bots.insert(1, "one".to_owned());
+
+        Config {
+            bots,
+            cache_dir: None,
+            listen: "127.0.0.1:0".to_owned(),
+            token: Some("top".to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_forward() {
+        let (config, upstream) = (config(), Fake::default());
+        let forward = |request: &str| {
+            let incoming = read_request(request.as_bytes()).unwrap().unwrap();
+
+            forward(&incoming, &config, &upstream).map(|resp| resp.status)
+        };
+
+        assert_eq!(forward("GET /api/bots/1 HTTP/1.1\r\n\r\n"), Ok(200));
+        assert_eq!(
+            forward(concat!(
+                "POST /bots/1/stats HTTP/1.1\r\n",
+                "Authorization: stolen\r\n",
+                "Content-Length: 2\r\n\r\n{}",
+            )),
+            Ok(200),
+        );
+        assert_eq!(
+            forward("GET /bots/1 HTTP/1.1\r\nAuthorization: stolen\r\n\r\n"),
+            Ok(200),
+        );
+
+        let error = |request| forward(request).unwrap_err().0;
+        assert_eq!(error("DELETE /bots/1 HTTP/1.1\r\n\r\n"), 405);
+        assert_eq!(error("GET /nope HTTP/1.1\r\n\r\n"), 404);
+        assert_eq!(error("GET /bots/2/votes HTTP/1.1\r\n\r\n"), 403);
+
+        let requests = upstream.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].url.ends_with("/bots/1"));
+        assert_eq!(requests[1].body.as_deref(), Some(&b"{}"[..]));
+
+        // The client's authorization is replaced or dropped, never forwarded.
+        let auth = |request: &Request| {
+            request
+                .headers
+                .iter()
+                .filter(|(name, _)| *name == "Authorization")
+                .map(|(_, value)| value.clone())
+                .collect::<Vec<_>>()
+        };
+        assert!(auth(&requests[0]).is_empty());
+        assert_eq!(auth(&requests[1]), ["one"]);
+        assert!(auth(&requests[2]).is_empty());
+    }
+
+    #[test]
+    fn test_read_request() {
+        let incoming = read_request(&b"GET /weekend HTTP/1.1\r\n\r\n"[..]);
+        let incoming = incoming.unwrap().unwrap();
+        assert_eq!((incoming.method.as_str(), incoming.target.as_str()), (
+            "GET",
+            "/weekend",
+        ));
+        assert!(read_request(&b""[..]).unwrap().is_none());
+
+        let invalid = |request: &str| read_request(request.as_bytes()).is_err();
+        assert!(invalid("GET\r\n\r\n"));
+        assert!(invalid("POST / HTTP/1.1\r\nContent-Length: x\r\n\r\n"));
+        assert!(invalid(
+            "POST / HTTP/1.1\r\nContent-Length: 1048577\r\n\r\n",
+        ));
+        assert!(invalid("POST / HTTP/1.1\r\nContent-Length: 4\r\n\r\n{}"));
+        assert!(invalid(concat!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            "2\r\n{}\r\n0\r\n\r\n",
+        )));
+
+        let header = format!("X-Padding: {}\r\n", "a".repeat(1000));
+        let head = |headers| {
+            format!("GET / HTTP/1.1\r\n{}\r\n", header.repeat(headers))
+        };
+        assert!(read_request(head(7).as_bytes()).is_ok());
+        assert!(invalid(&head(9)));
+        let target = "a".repeat(8192);
+        assert!(invalid(&format!("GET /{} HTTP/1.1\r\n\r\n", target)));
+    }
+}
@@ -0,0 +1,101 @@
+//! Resolution of the API token from the command line, environment or config
+//! file.
+
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+/// The contents of the config file.
+#[derive(Debug, Default, Deserialize)]
+struct Config {
+    /// The API token.
+    token: Option<String>,
+}
+
+/// The default config file location, such as `~/.config/dbl/config.toml`.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("dbl").join("config.toml"))
+}
+
+/// Resolves the API token.
+///
+/// A token passed by flag takes precedence over one from the environment,
+/// which takes precedence over one in the config file. Empty tokens are
+/// ignored. A missing config file is only an error if its path was given
+/// explicitly.
+pub fn token(
+    flag: Option<String>,
+    env: Option<String>,
+    path: Option<&Path>,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let token = flag
+        .filter(|token| !token.is_empty())
+        .or_else(|| env.filter(|token| !token.is_empty()));
+
+    if token.is_some() {
+        return Ok(token);
+    }
+
+    let (path, explicit) = match path {
+        Some(path) => (path.to_owned(), true),
+        None => match default_path() {
+            Some(path) => (path, false),
+            None => return Ok(None),
+        },
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(why) if why.kind() == ErrorKind::NotFound && !explicit => {
+            return Ok(None);
+        },
+        Err(why) => {
+            return Err(format!("reading {}: {}", path.display(), why).into());
+        },
+    };
+
+    let config = toml::from_str::<Config>(&contents)
+        .map_err(|why| format!("parsing {}: {}", path.display(), why))?;
+
+    Ok(config.token.filter(|token| !token.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+    use super::token;
+
+    #[test]
+    fn test_token() {
+        let path = env::temp_dir()
+            .join(format!("dbl-config-{}.toml", process::id()));
+        fs::write(&path, "token = \"from-file\"\n").unwrap();
+
+        let from_flag = || Some("from-flag".to_owned());
+        let from_env = || Some("from-env".to_owned());
+
+        // A token from the flag takes precedence, then the environment.
+        let resolved = token(from_flag(), from_env(), Some(&path));
+        assert_eq!(resolved.unwrap().as_deref(), Some("from-flag"));
+
+        let resolved = token(Some(String::new()), from_env(), Some(&path));
+        assert_eq!(resolved.unwrap().as_deref(), Some("from-env"));
+
+        let resolved = token(None, Some(String::new()), Some(&path));
+        assert_eq!(resolved.unwrap().as_deref(), Some("from-file"));
+
+        fs::write(&path, "token = \"\"\n").unwrap();
+        assert_eq!(token(None, None, Some(&path)).unwrap(), None);
+
+        fs::write(&path, "token = 1\n").unwrap();
+        assert!(token(None, None, Some(&path)).is_err());
+
+        // A config file given explicitly must exist.
+        fs::remove_file(&path).unwrap();
+        assert!(token(None, None, Some(&path)).is_err());
+    }
+}
@@ -0,0 +1,463 @@
+//! A command-line tool for the Discord Bot List API, built on the sync
+//! reqwest client.
+//!
+//! Commands that require authorization take the API token from the `--token`
+//! flag, the `DBL_TOKEN` environment variable, or the `token` key of a TOML
+//! config file, in that order. The config file defaults to `dbl/config.toml`
+//! in the platform's config directory, and can be set with `--config`.
+
+mod config;
+mod output;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use discord_bots_org::{
+    builder::widget::{LargeWidget, SmallWidget},
+    model::{BotVotes, ShardStats, Webhook, WebhookType},
+    webhook::Sender,
+    ReqwestSyncClient as ApiClient,
+};
+use reqwest::blocking::Client as ReqwestClient;
+use std::{
+    convert::TryInto,
+    env,
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+    process,
+    sync::Arc,
+};
+use self::output::Format;
+
+/// The columns shown when printing a list of bots as a table.
+const BOT_COLUMNS: &[&str] = &[
+    "id",
+    "username",
+    "points",
+    "lib",
+    "certifiedBot",
+];
+
+/// The columns shown when printing a list of users as a table.
+const USER_COLUMNS: &[&str] = &["id", "username", "discriminator"];
+
+/// Command-line tool for the Discord Bot List API.
+#[derive(Debug, Parser)]
+#[command(name = "dbl", version)]
+struct Cli {
+    /// The API token, required by `stats post`, `votes` and `vote-check`.
+    ///
+    /// Defaults to the `DBL_TOKEN` environment variable.
+    #[arg(long, global = true)]
+    token: Option<String>,
+    /// The path to a TOML config file containing a `token` key.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// The format to print output in.
+    #[arg(
+        default_value_t = Format::Table,
+        global = true,
+        long,
+        short,
+        value_enum,
+    )]
+    output: Format,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Retrieves information about a bot.
+    Bot {
+        /// The ID of the bot.
+        id: u64,
+    },
+    /// Searches for bots.
+    Search(SearchArgs),
+    /// Retrieves or posts a bot's stats.
+    #[command(subcommand)]
+    Stats(StatsCommand),
+    /// Retrieves who has voted for a bot.
+    Votes {
+        /// The ID of the bot.
+        bot_id: u64,
+    },
+    /// Checks whether a user has voted for a bot.
+    VoteCheck {
+        /// The ID of the bot.
+        bot_id: u64,
+        /// The ID of the user.
+        user_id: u64,
+    },
+    /// Retrieves information about a user.
+    User {
+        /// The ID of the user.
+        id: u64,
+    },
+    /// Sends or replays webhooks to test a webhook handler.
+    #[command(subcommand)]
+    Webhook(WebhookCommand),
+    /// Creates the URL of a bot's widget.
+    Widget(WidgetArgs),
+}
+
+#[derive(Args, Debug)]
+struct SearchArgs {
+    /// A search query string.
+    query: Option<String>,
+    /// The amount of bots to return, at most 500.
+    #[arg(long)]
+    limit: Option<u16>,
+    /// The amount of bots to skip.
+    #[arg(long)]
+    offset: Option<u64>,
+    /// The field to sort by, descending unless `--ascending` is given.
+    #[arg(long)]
+    sort: Option<String>,
+    /// Sorts in ascending order.
+    #[arg(long, requires = "sort")]
+    ascending: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum StatsCommand {
+    /// Retrieves a bot's stats.
+    Get {
+        /// The ID of the bot.
+        bot_id: u64,
+    },
+    /// Posts a bot's stats.
+    ///
+    /// Either the total server count, the server count of a single shard, or
+    /// the server counts of all shards can be posted.
+    Post {
+        /// The ID of the bot.
+        bot_id: u64,
+        /// The total number of servers, or of the shard if `--shard-id` is
+        /// given.
+        #[arg(long, required_unless_present = "shards")]
+        servers: Option<u64>,
+        /// The total number of shards.
+        #[arg(long)]
+        shard_count: Option<u64>,
+        /// The ID of the shard being posted for.
+        #[arg(long, requires = "shard_count")]
+        shard_id: Option<u64>,
+        /// The number of servers in each shard, in order of shard ID.
+        #[arg(
+            long,
+            conflicts_with_all = ["servers", "shard_count", "shard_id"],
+            value_delimiter = ',',
+        )]
+        shards: Option<Vec<u64>>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum WebhookCommand {
+    /// Sends a single webhook.
+    Send {
+        #[command(flatten)]
+        target: WebhookTarget,
+        /// The ID of the bot that was voted for.
+        #[arg(long)]
+        bot: u64,
+        /// The ID of the user who voted.
+        #[arg(long)]
+        user: u64,
+        /// The type of the vote.
+        #[arg(default_value_t = WebhookKind::Test, long = "type", value_enum)]
+        kind: WebhookKind,
+        /// Marks the vote as made during the weekend multiplier.
+        #[arg(long)]
+        weekend: bool,
+        /// The query string of the vote page, such as `?a=1&b=2`.
+        #[arg(default_value = "", long)]
+        query: String,
+    },
+    /// Replays a JSON-lines recording of webhooks.
+    ///
+    /// Each line is a webhook payload, optionally with a `receivedAt`
+    /// timestamp. The time between timestamped webhooks is kept.
+    Replay {
+        #[command(flatten)]
+        target: WebhookTarget,
+        /// The recording to replay, or `-` to read from stdin.
+        file: PathBuf,
+        /// How many times faster than recorded to replay, or `inf` to send
+        /// every webhook immediately.
+        #[arg(default_value_t = 1.0, long)]
+        speed: f64,
+    },
+}
+
+#[derive(Args, Debug)]
+struct WebhookTarget {
+    /// The URL of the webhook handler.
+    url: String,
+    /// The value of the `Authorization` header, as configured for the bot.
+    #[arg(
+        default_value = "",
+        env = "DBL_WEBHOOK_AUTH",
+        hide_env_values = true,
+        long,
+    )]
+    auth: String,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum WebhookKind {
+    /// A test webhook.
+    Test,
+    /// A vote.
+    Upvote,
+}
+
+#[derive(Args, Debug)]
+struct WidgetArgs {
+    /// The ID of the bot.
+    bot_id: u64,
+    /// Creates a small widget instead of a large one.
+    #[arg(long)]
+    small: bool,
+    /// Creates a PNG widget instead of an SVG one.
+    #[arg(long)]
+    png: bool,
+    /// Sets a colour, such as `top=FF0000`.
+    ///
+    /// Large widgets accept `top`, `middle`, `username`, `certified`, `data`
+    /// and `label`. Small widgets accept `avatar-background`, `left`,
+    /// `left-text`, `right` and `right-text`.
+    #[arg(
+        long = "colour",
+        value_name = "NAME=HEX",
+        value_parser = parse_colour,
+    )]
+    colours: Vec<(String, String)>,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    if let Err(why) = run(cli) {
+        eprintln!("error: {}", why);
+
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let Cli {
+        command,
+        config,
+        output: format,
+        token,
+    } = cli;
+    let client = ApiClient::new(Arc::new(ReqwestClient::new()));
+    let token = || -> Result<String, Box<dyn Error>> {
+        let env = env::var("DBL_TOKEN").ok();
+        let token = config::token(token.clone(), env, config.as_deref())?;
+
+        token.ok_or_else(|| {
+            let path = config
+                .clone()
+                .or_else(config::default_path)
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "the config file".to_owned());
+
+            format!(
+                "an API token is required: pass --token, set DBL_TOKEN, or \
+                 set `token` in {}",
+                path,
+            )
+            .into()
+        })
+    };
+
+    match command {
+        Command::Bot { id } => output::print(format, &client.get_bot(id)?),
+        Command::Search(args) => {
+            let resp = client.get_bots(|mut search| {
+                if let Some(query) = args.query {
+                    search.search(query);
+                }
+
+                if let Some(limit) = args.limit {
+                    search.limit(limit);
+                }
+
+                if let Some(offset) = args.offset {
+                    search.offset(offset);
+                }
+
+                if let Some(sort) = args.sort {
+                    search.sort(sort, args.ascending);
+                }
+
+                search
+            })?;
+
+            if format == Format::Table {
+                output::print_with_columns(format, &resp.results, BOT_COLUMNS)?;
+                println!("\nShowing {} of {} bots.", resp.count, resp.total);
+
+                Ok(())
+            } else {
+                output::print(format, &resp)
+            }
+        },
+        Command::Stats(StatsCommand::Get { bot_id }) => {
+            output::print(format, &client.get_bot_stats(bot_id)?)
+        },
+        Command::Stats(StatsCommand::Post {
+            bot_id,
+            servers,
+            shard_count,
+            shard_id,
+            shards,
+        }) => {
+            let stats = match (shards, shard_id, servers) {
+                (Some(shards), _, _) => ShardStats::Shards(shards),
+                (None, Some(shard_id), Some(servers)) => ShardStats::Shard {
+                    guild_count: servers.try_into().map_err(|_| {
+                        "a single shard can have at most 65535 servers"
+                    })?,
+                    shard_count: shard_count.unwrap_or_default(),
+                    shard_id,
+                },
+                (None, None, Some(servers)) => ShardStats::Cumulative {
+                    guild_count: servers,
+                    shard_count,
+                },
+                (None, _, None) => return Err("--servers is required".into()),
+            };
+
+            client.post_stats(token()?, bot_id, &stats)?;
+
+            output::print(format, &stats)
+        },
+        Command::Votes { bot_id } => {
+            match client.get_bot_votes(token()?, bot_id)? {
+                BotVotes::Ids(ids) => output::print(format, &ids),
+                BotVotes::Users(users) => {
+                    output::print_with_columns(format, &users, USER_COLUMNS)
+                },
+            }
+        },
+        Command::VoteCheck { bot_id, user_id } => {
+            let voted = client.get_bot_vote_check(token()?, bot_id, user_id)?;
+
+            output::print(format, &voted)
+        },
+        Command::User { id } => output::print(format, &client.get_user(id)?),
+        Command::Webhook(command) => webhook(format, command),
+        Command::Widget(args) => output::print(format, &widget(args)?),
+    }
+}
+
+fn webhook(
+    format: Format,
+    command: WebhookCommand,
+) -> Result<(), Box<dyn Error>> {
+    let client = Arc::new(ReqwestClient::new());
+
+    match command {
+        WebhookCommand::Send {
+            target,
+            bot,
+            user,
+            kind,
+            weekend,
+            query,
+        } => {
+            let webhook = Webhook {
+                bot: bot.to_string(),
+                is_weekend: weekend,
+                kind: match kind {
+                    WebhookKind::Test => WebhookType::Test,
+                    WebhookKind::Upvote => WebhookType::Upvote,
+                },
+                query,
+                user_id: user.to_string(),
+            };
+
+            Sender::new(client, target.url, target.auth)?.send(&webhook)?;
+
+            output::print(format, &webhook)
+        },
+        WebhookCommand::Replay {
+            target,
+            file,
+            speed,
+        } => {
+            if speed.is_nan() || speed <= 0.0 {
+                return Err("--speed must be positive".into());
+            }
+
+            let reader: Box<dyn BufRead> = if file.as_os_str() == "-" {
+                Box::new(BufReader::new(io::stdin()))
+            } else {
+                Box::new(BufReader::new(File::open(&file)?))
+            };
+
+            let sender = Sender::new(client, target.url, target.auth)?;
+
+            output::print(format, &sender.replay(reader, speed)?)
+        },
+    }
+}
+
+fn widget(args: WidgetArgs) -> Result<String, Box<dyn Error>> {
+    if args.small {
+        let mut widget = SmallWidget::new(args.bot_id);
+        widget.png(args.png);
+
+        for (name, value) in args.colours {
+            match name.as_str() {
+                "avatar-background" => widget.avatar_background(value),
+                "left" => widget.left_color(value),
+                "left-text" => widget.left_text_color(value),
+                "right" => widget.right_color(value),
+                "right-text" => widget.right_text_color(value),
+                other => {
+                    let why = format!("unknown small colour `{}`", other);
+
+                    return Err(why.into());
+                },
+            };
+        }
+
+        Ok(widget.build()?)
+    } else {
+        let mut widget = LargeWidget::new(args.bot_id);
+        widget.png(args.png);
+
+        for (name, value) in args.colours {
+            match name.as_str() {
+                "certified" => widget.certified_color(value),
+                "data" => widget.data_color(value),
+                "label" => widget.label_color(value),
+                "middle" => widget.middle_color(value),
+                "top" => widget.top_color(value),
+                "username" => widget.username_color(value),
+                other => {
+                    let why = format!("unknown large colour `{}`", other);
+
+                    return Err(why.into());
+                },
+            };
+        }
+
+        Ok(widget.build()?)
+    }
+}
+
+fn parse_colour(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, hex)) if !name.is_empty() && !hex.is_empty() => {
+            Ok((name.to_owned(), hex.trim_start_matches('#').to_owned()))
+        },
+        _ => Err("expected NAME=HEX".to_owned()),
+    }
+}
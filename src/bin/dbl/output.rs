@@ -0,0 +1,200 @@
+//! Rendering of command output as a table, JSON or YAML.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+
+/// The format to print output in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    /// A human-readable table.
+    Table,
+    /// Pretty-printed JSON.
+    Json,
+    /// YAML.
+    Yaml,
+}
+
+/// Prints a value in the given format.
+///
+/// As a table, objects are printed as key-value pairs and lists of objects
+/// are printed with a row per item.
+pub fn print(
+    format: Format,
+    value: &impl Serialize,
+) -> Result<(), Box<dyn Error>> {
+    print_with_columns(format, value, &[])
+}
+
+/// Prints a value in the given format, limiting the columns of tables of
+/// lists to those given.
+///
+/// If no columns are given, all fields of the first item are used.
+pub fn print_with_columns(
+    format: Format,
+    value: &impl Serialize,
+    columns: &[&str],
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        Format::Yaml => print!("{}", serde_yaml::to_string(value)?),
+        Format::Table => {
+            print!("{}", table(&serde_json::to_value(value)?, columns));
+        },
+    }
+
+    Ok(())
+}
+
+fn table(value: &Value, columns: &[&str]) -> String {
+    match value {
+        Value::Array(items) if items.iter().all(Value::is_object) => {
+            let columns = if columns.is_empty() {
+                items
+                    .first()
+                    .and_then(Value::as_object)
+                    .map(|item| item.keys().map(String::as_str).collect())
+                    .unwrap_or_default()
+            } else {
+                columns.to_vec()
+            };
+
+            let rows = items
+                .iter()
+                .map(|item| {
+                    columns
+                        .iter()
+                        .map(|column| {
+                            cell(item.get(*column).unwrap_or(&Value::Null))
+                        })
+                        .collect()
+                })
+                .collect::<Vec<_>>();
+
+            let header = columns.iter().map(|c| (*c).to_owned()).collect();
+
+            render(Some(header), &rows)
+        },
+        Value::Array(items) => {
+            items.iter().map(|item| cell(item) + "\n").collect()
+        },
+        Value::Object(fields) => {
+            let rows = fields
+                .iter()
+                .map(|(key, value)| vec![key.clone(), cell(value)])
+                .collect::<Vec<_>>();
+
+            render(None, &rows)
+        },
+        other => cell(other) + "\n",
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.replace('\n', " "),
+        Value::Array(items) => {
+            items.iter().map(cell).collect::<Vec<_>>().join(", ")
+        },
+        other => other.to_string(),
+    }
+}
+
+fn render(header: Option<Vec<String>>, rows: &[Vec<String>]) -> String {
+    let all = header.iter().chain(rows);
+    let mut widths = Vec::new();
+
+    for row in all.clone() {
+        for (i, value) in row.iter().enumerate() {
+            let len = value.chars().count();
+
+            match widths.get_mut(i) {
+                Some(width) if *width < len => *width = len,
+                Some(_) => {},
+                None => widths.push(len),
+            }
+        }
+    }
+
+    let mut out = String::new();
+
+    for (i, row) in all.enumerate() {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:width$}", value, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        out.push_str(line.trim_end());
+        out.push('\n');
+
+        if i == 0 && header.is_some() {
+            let rule = widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<_>>()
+                .join("  ");
+
+            out.push_str(&rule);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::{render, table};
+
+    #[test]
+    fn test_render() {
+        let header = vec!["id".to_owned(), "username".to_owned()];
+        let rows = vec![
+            vec!["1".to_owned(), "Bot".to_owned()],
+            vec!["270198738570444801".to_owned(), String::new()],
+        ];
+
+        assert_eq!(
+            render(Some(header), &rows),
+            "id                  username\n\
+             ------------------  --------\n\
+             1                   Bot\n\
+             270198738570444801\n",
+        );
+        assert_eq!(render(None, &rows[..1]), "1  Bot\n");
+    }
+
+    #[test]
+    fn test_table() {
+        let bots = json!([
+            { "id": "1", "tags": ["Fun", "Music"], "username": "A" },
+            { "id": "22", "prefix": "!", "username": "Bot\nB" },
+        ]);
+
+        assert_eq!(
+            table(&bots, &["id", "username", "prefix"]),
+            "id  username  prefix\n\
+             --  --------  ------\n\
+             1   A\n\
+             22  Bot B     !\n",
+        );
+        assert_eq!(
+            table(&bots, &[]),
+            "id  tags        username\n\
+             --  ----------  --------\n\
+             1   Fun, Music  A\n\
+             22              Bot B\n",
+        );
+        assert_eq!(
+            table(&json!({ "id": "1", "points": 10 }), &[]),
+            "id      1\npoints  10\n",
+        );
+        assert_eq!(table(&json!(["a", 1]), &[]), "a\n1\n");
+        assert_eq!(table(&json!(true), &[]), "true\n");
+    }
+}
@@ -0,0 +1,670 @@
+//! Caching of responses in front of any backend.
+//!
+//! # Examples
+//!
+//! Refer to the documentation for [`CachedBackend`].
+//!
+//! [`CachedBackend`]: struct.CachedBackend.html
+
+use chrono::{DateTime, Duration, Utc};
+use crate::{
+    route::{Method, Route},
+    vote::{Clock, SystemClock},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{Debug, Formatter, Result as FmtResult},
+    fs,
+    future::Future,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+};
+use super::{
+    operation::{Request, Response},
+    AsyncBackend,
+    Backend,
+};
+
+type Task = Pin<Box<dyn Future<Output = ()> + Send>>;
+type Spawner = Arc<dyn Fn(Task) + Send + Sync>;
+
+/// A cache of responses to routes, with a TTL per route.
+///
+/// Responses are kept in a bounded memory store, evicting the least recently
+/// used, and optionally in a directory on disk, which outlives the process.
+///
+/// Only successful responses to `GET` routes with a TTL which don't require
+/// authorization are cached. By default these are bots and users for 5
+/// minutes, and bot stats for 1 minute.
+///
+/// When an expired response has an `ETag` or `Last-Modified` header, it is
+/// revalidated with a conditional request, and kept if the API responds with
+/// a 304.
+#[derive(Debug)]
+pub struct Cache<C = SystemClock> {
+    capacity: usize,
+    clock: C,
+    dir: Option<PathBuf>,
+    state: Mutex<State>,
+    stale: Duration,
+    ttls: HashMap<&'static str, Duration>,
+}
+
+impl Cache {
+    /// Creates a new cache with the default TTLs and a capacity of 1000
+    /// responses.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<C: Clock> Cache<C> {
+    /// Creates a new cache which uses the given clock for expiry.
+    pub fn with_clock(clock: C) -> Self {
+        let mut ttls = HashMap::new();
+        ttls.insert(Route::GetBot { id: 0 }.template(), Duration::minutes(5));
+        ttls.insert(Route::GetUser { id: 0 }.template(), Duration::minutes(5));
+
+        let stats = Route::GetBotStats { id: 0 }.template();
+        ttls.insert(stats, Duration::minutes(1));
+
+        Self {
+            capacity: 1000,
+            clock,
+            dir: None,
+            state: Mutex::new(State::default()),
+            stale: Duration::zero(),
+            ttls,
+        }
+    }
+
+    /// Sets the maximum number of responses kept in memory.
+    ///
+    /// Defaults to 1000.
+    pub fn capacity(&mut self, capacity: usize) -> &mut Self {
+        self.capacity = capacity.max(1);
+
+        self
+    }
+
+    /// Sets a directory to also keep responses in, which is created if it
+    /// doesn't exist.
+    ///
+    /// Writing to the directory is best-effort, and failures are ignored.
+    pub fn dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        let dir = dir.into();
+        let _ = fs::create_dir_all(&dir);
+        self.dir = Some(dir);
+
+        self
+    }
+
+    /// Sets how long after expiring a response may still be returned while
+    /// it is revalidated in the background.
+    ///
+    /// Defaults to zero, so expired responses are always revalidated before
+    /// returning.
+    pub fn stale_while_revalidate(&mut self, stale: Duration) -> &mut Self {
+        self.stale = stale;
+
+        self
+    }
+
+    /// Sets the TTL of responses to routes with the given [template], or
+    /// disables caching them if the TTL is zero.
+    ///
+    /// [template]: ../../route/enum.Route.html#method.template
+    pub fn ttl(&mut self, template: &'static str, ttl: Duration) -> &mut Self {
+        if ttl > Duration::zero() {
+            self.ttls.insert(template, ttl);
+        } else {
+            self.ttls.remove(template);
+        }
+
+        self
+    }
+
+    fn lookup(&self, request: &Request) -> Lookup {
+        let ttl = match self.ttl_of(&request.route) {
+            Some(ttl) if request.method == Method::Get => ttl,
+            _ => return Lookup::Fetch(request.clone(), None),
+        };
+
+        let entry = match self.get(&request.url) {
+            Some(entry) => entry,
+            None => return Lookup::Fetch(request.clone(), None),
+        };
+        let age = self.clock.now() - entry.fetched_at;
+
+        if age < ttl {
+            return Lookup::Fresh(entry.response());
+        }
+
+        let revalidation = entry.conditional(request);
+
+        if age >= ttl + self.stale {
+            return Lookup::Fetch(revalidation, Some(entry));
+        }
+
+        let mut state = self.lock();
+
+        if state.revalidating.insert(request.url.clone()) {
+            Lookup::Stale(entry.response(), Some((revalidation, entry)))
+        } else {
+            Lookup::Stale(entry.response(), None)
+        }
+    }
+
+    /// Caches the result of a request, where `cached` is the entry that a
+    /// revalidation was made from.
+    ///
+    /// The entry is passed along rather than looked up again, as it may have
+    /// been evicted while the request was in flight.
+    fn finish(
+        &self,
+        request: &Request,
+        cached: Option<Entry>,
+        result: Result<Response>,
+    ) -> Result<Response> {
+        self.lock().revalidating.remove(&request.url);
+        let response = result?;
+
+        if self.ttl_of(&request.route).is_none() {
+            return Ok(response);
+        }
+
+        let entry = match response.status {
+            304 => match cached {
+                Some(entry) => Entry {
+                    fetched_at: self.clock.now(),
+                    ..entry
+                },
+                None => return Ok(response),
+            },
+            200..=299 => Entry {
+                body: response.body.clone(),
+                etag: response.header("etag").map(ToOwned::to_owned),
+                fetched_at: self.clock.now(),
+                last_modified: response
+                    .header("last-modified")
+                    .map(ToOwned::to_owned),
+            },
+            _ => return Ok(response),
+        };
+
+        let response = entry.response();
+        self.insert(&request.url, entry);
+
+        Ok(response)
+    }
+
+    fn get(&self, url: &str) -> Option<Entry> {
+        if let Some(entry) = self.lock().get(url) {
+            return Some(entry);
+        }
+
+        let path = self.path(url)?;
+        let entry = serde_json::from_slice::<Entry>(&fs::read(path).ok()?);
+        let entry = entry.ok()?;
+        self.lock().insert(url, entry.clone(), self.capacity);
+
+        Some(entry)
+    }
+
+    fn insert(&self, url: &str, entry: Entry) {
+        if let Some(path) = self.path(url) {
+            if let Ok(contents) = serde_json::to_vec(&entry) {
+                let tmp = path.with_extension("tmp");
+
+                if fs::write(&tmp, contents).is_ok() {
+                    let _ = fs::rename(tmp, path);
+                }
+            }
+        }
+
+        self.lock().insert(url, entry, self.capacity);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    fn path(&self, url: &str) -> Option<PathBuf> {
+        let hash = Sha256::digest(url.as_bytes());
+        let name = hash
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+
+        self.dir.as_ref().map(|dir| dir.join(name + ".json"))
+    }
+
+    fn ttl_of(&self, route: &Route) -> Option<Duration> {
+        if route.requires_auth() {
+            return None;
+        }
+
+        self.ttls.get(route.template()).copied()
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A backend which caches responses from another backend with a [`Cache`].
+///
+/// This implements [`Backend`] when the inner backend does, revalidating
+/// stale responses on a new thread, and [`AsyncBackend`] when the inner
+/// backend does, revalidating them with a [`spawner`] if one is set.
+///
+/// # Examples
+///
+/// Cache bots for 10 minutes in front of a client:
+///
+/// ```rust,no_run
+/// use chrono::Duration;
+/// use discord_bots_org::{
+///     bridge::{cache::{Cache, CachedBackend}, Client},
+///     route::Route,
+/// };
+/// use reqwest::blocking::Client as ReqwestClient;
+///
+/// let mut cache = Cache::new();
+/// cache
+///     .ttl(Route::GetBot { id: 0 }.template(), Duration::minutes(10))
+///     .stale_while_revalidate(Duration::minutes(1));
+///
+/// let client = Client::new(CachedBackend::new(ReqwestClient::new(), cache));
+/// let bot = client.get_bot(270_198_738_570_444_801)?;
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// [`AsyncBackend`]: ../trait.AsyncBackend.html
+/// [`Backend`]: ../trait.Backend.html
+/// [`Cache`]: struct.Cache.html
+/// [`spawner`]: #method.spawner
+pub struct CachedBackend<B, C = SystemClock> {
+    cache: Arc<Cache<C>>,
+    inner: Arc<B>,
+    spawner: Option<Spawner>,
+}
+
+impl<B, C> CachedBackend<B, C> {
+    /// Creates a new backend caching responses from another.
+    pub fn new(inner: B, cache: Cache<C>) -> Self {
+        Self {
+            cache: Arc::new(cache),
+            inner: Arc::new(inner),
+            spawner: None,
+        }
+    }
+
+    /// Sets a function to spawn background revalidations of stale responses
+    /// when used as an [`AsyncBackend`], such as `tokio::spawn`.
+    ///
+    /// Without a spawner, stale responses are revalidated before returning,
+    /// and only returned if revalidating them fails.
+    ///
+    /// [`AsyncBackend`]: ../trait.AsyncBackend.html
+    pub fn spawner<F>(&mut self, spawner: F) -> &mut Self
+    where
+        F: Fn(Task) + Send + Sync + 'static,
+    {
+        self.spawner = Some(Arc::new(spawner));
+
+        self
+    }
+}
+
+impl<B, C> Clone for CachedBackend<B, C> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            inner: Arc::clone(&self.inner),
+            spawner: self.spawner.clone(),
+        }
+    }
+}
+
+impl<B: Debug, C: Debug> Debug for CachedBackend<B, C> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("CachedBackend")
+            .field("cache", &self.cache)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<B, C> Backend for CachedBackend<B, C>
+where
+    B: Backend + Send + Sync + 'static,
+    C: Clock + Send + Sync + 'static,
+{
+    fn execute(&self, request: &Request) -> Result<Response> {
+        match self.cache.lookup(request) {
+            Lookup::Fetch(request, cached) => {
+                let result = self.inner.execute(&request);
+
+                self.cache.finish(&request, cached, result)
+            },
+            Lookup::Fresh(response) | Lookup::Stale(response, None) => {
+                Ok(response)
+            },
+            Lookup::Stale(response, Some((request, cached))) => {
+                let cache = Arc::clone(&self.cache);
+                let inner = Arc::clone(&self.inner);
+
+                thread::spawn(move || {
+                    let result = inner.execute(&request);
+                    let _ = cache.finish(&request, Some(cached), result);
+                });
+
+                Ok(response)
+            },
+        }
+    }
+}
+
+impl<B, C> AsyncBackend for CachedBackend<B, C>
+where
+    B: AsyncBackend + Send + Sync + 'static,
+    C: Clock + Send + Sync + 'static,
+{
+    fn execute(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response>> + Send {
+        let lookup = self.cache.lookup(request);
+        let backend = self.clone();
+
+        async move {
+            let (cache, inner) = (backend.cache, backend.inner);
+
+            match (lookup, backend.spawner) {
+                (Lookup::Fetch(request, cached), _) => {
+                    let result = inner.execute(&request).await;
+
+                    cache.finish(&request, cached, result)
+                },
+                (Lookup::Fresh(response), _)
+                | (Lookup::Stale(response, None), _) => Ok(response),
+                (Lookup::Stale(response, Some(stale)), Some(spawn)) => {
+                    let (request, cached) = stale;
+
+                    spawn(Box::pin(async move {
+                        let result = inner.execute(&request).await;
+                        let _ = cache.finish(&request, Some(cached), result);
+                    }));
+
+                    Ok(response)
+                },
+                (Lookup::Stale(response, Some(stale)), None) => {
+                    let (request, cached) = stale;
+                    let result = inner.execute(&request).await;
+                    let result = cache.finish(&request, Some(cached), result);
+
+                    Ok(result.unwrap_or(response))
+                },
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    body: Vec<u8>,
+    etag: Option<String>,
+    fetched_at: DateTime<Utc>,
+    last_modified: Option<String>,
+}
+
+impl Entry {
+    fn conditional(&self, request: &Request) -> Request {
+        let mut request = request.clone();
+
+        if let Some(etag) = &self.etag {
+            request.headers.push(("If-None-Match", etag.clone()));
+        }
+
+        if let Some(last_modified) = &self.last_modified {
+            request
+                .headers
+                .push(("If-Modified-Since", last_modified.clone()));
+        }
+
+        request
+    }
+
+    fn response(&self) -> Response {
+        let mut headers = Vec::new();
+
+        if let Some(etag) = &self.etag {
+            headers.push(("etag".to_owned(), etag.clone()));
+        }
+
+        if let Some(last_modified) = &self.last_modified {
+            headers.push(("last-modified".to_owned(), last_modified.clone()));
+        }
+
+        Response {
+            body: self.body.clone(),
+            headers,
+            status: 200,
+        }
+    }
+}
+
+/// The result of looking up a request in the cache.
+///
+/// Revalidations carry the entry they were made from, to be refreshed by a
+/// 304 even if it has since been evicted.
+enum Lookup {
+    Fetch(Request, Option<Entry>),
+    Fresh(Response),
+    Stale(Response, Option<(Request, Entry)>),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<String, (u64, Entry)>,
+    recency: BTreeMap<u64, String>,
+    revalidating: HashSet<String>,
+    tick: u64,
+}
+
+impl State {
+    fn get(&mut self, url: &str) -> Option<Entry> {
+        self.tick += 1;
+        let (used, entry) = self.entries.get_mut(url)?;
+        self.recency.remove(used);
+        self.recency.insert(self.tick, url.to_owned());
+        *used = self.tick;
+
+        Some(entry.clone())
+    }
+
+    fn insert(&mut self, url: &str, entry: Entry, capacity: usize) {
+        self.tick += 1;
+
+        if let Some((used, _)) = self.entries.remove(url) {
+            self.recency.remove(&used);
+        }
+
+        self.entries.insert(url.to_owned(), (self.tick, entry));
+        self.recency.insert(self.tick, url.to_owned());
+
+        while self.entries.len() > capacity {
+            match self.recency.pop_first() {
+                Some((_, oldest)) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use crate::{
+        bridge::{
+            operation::{self, Request, Response},
+            Backend,
+        },
+        vote::ManualClock,
+        Result,
+    };
+    use std::{env, fs, process, sync::Mutex};
+    use super::{Cache, CachedBackend, Lookup};
+
+    #[derive(Debug, Default)]
+    struct Fake {
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl Backend for Fake {
+        fn execute(&self, request: &Request) -> Result<Response> {
+            let mut requests = self.requests.lock().unwrap();
+            let conditional = request
+                .headers
+                .iter()
+                .any(|(name, _)| *name == "If-None-Match");
+            let status = if conditional { 304 } else { 200 };
+            requests.push(request.clone());
+
+            Ok(Response {
+                body: format!("{}", requests.len()).into_bytes(),
+                headers: vec![("ETag".to_owned(), "\"v1\"".to_owned())],
+                status,
+            })
+        }
+    }
+
+    fn clock() -> ManualClock {
+        ManualClock::new(Utc.timestamp_opt(1_546_300_800, 0).unwrap())
+    }
+
+    #[test]
+    fn test_backend() -> Result<()> {
+        let clock = clock();
+        let mut cache = Cache::with_clock(clock.clone());
+        cache.capacity(1);
+        let backend = CachedBackend::new(Fake::default(), cache);
+        let bot = operation::get_bot(1)?.request;
+
+        assert_eq!(backend.execute(&bot)?.body, b"1");
+        assert_eq!(backend.execute(&bot)?.body, b"1");
+
+        // Expired responses with an ETag are revalidated, keeping the body.
+        clock.advance(Duration::minutes(5));
+        let response = backend.execute(&bot)?;
+        assert_eq!((response.status, response.body), (200, b"1".to_vec()));
+
+        // Routes requiring authorization are never cached.
+        let votes = operation::get_bot_votes("auth", 1)?.request;
+        backend.execute(&votes)?;
+        backend.execute(&votes)?;
+
+        // Caching another route evicts the least recently used one.
+        backend.execute(&operation::get_user(2)?.request)?;
+        backend.execute(&bot)?;
+
+        let requests = backend.inner.requests.lock().unwrap();
+        assert_eq!(requests.len(), 6);
+        let etag = ("If-None-Match", "\"v1\"".to_owned());
+        assert!(requests[1].headers.contains(&etag));
+        assert!(requests[5].headers.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stale_while_revalidate() -> Result<()> {
+        let clock = clock();
+        let mut cache = Cache::with_clock(clock.clone());
+        cache.stale_while_revalidate(Duration::minutes(1));
+        let request = operation::get_bot_stats(1)?.request;
+        let response = |body: &[u8]| Response {
+            body: body.to_vec(),
+            headers: Vec::new(),
+            status: 200,
+        };
+
+        cache.finish(&request, None, Ok(response(b"1")))?;
+        clock.advance(Duration::seconds(90));
+
+        // Only the first lookup of a stale response starts a revalidation.
+        assert!(matches!(
+            cache.lookup(&request),
+            Lookup::Stale(Response { .. }, Some(_)),
+        ));
+        assert!(matches!(cache.lookup(&request), Lookup::Stale(_, None)));
+
+        cache.finish(&request, None, Ok(response(b"2")))?;
+        assert!(matches!(cache.lookup(&request), Lookup::Fresh(_)));
+
+        clock.advance(Duration::minutes(2));
+        assert!(matches!(cache.lookup(&request), Lookup::Fetch(..)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_evicted_revalidation() -> Result<()> {
+        let clock = clock();
+        let mut cache = Cache::with_clock(clock.clone());
+        cache.capacity(1);
+        let bot = operation::get_bot(1)?.request;
+        let fake = Fake::default();
+
+        cache.finish(&bot, None, fake.execute(&bot))?;
+        clock.advance(Duration::minutes(5));
+
+        let (request, cached) = match cache.lookup(&bot) {
+            Lookup::Fetch(request, cached) => (request, cached),
+            _ => panic!("expired response not revalidated"),
+        };
+
+        // Another response evicts the entry while revalidating it.
+        let user = operation::get_user(2)?.request;
+        cache.finish(&user, None, fake.execute(&user))?;
+
+        let response = cache.finish(&request, cached, fake.execute(&request))?;
+        assert_eq!((response.status, response.body), (200, b"1".to_vec()));
+        assert!(matches!(cache.lookup(&bot), Lookup::Fresh(_)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dir() -> Result<()> {
+        let dir = env::temp_dir()
+            .join(format!("dbl-cache-{}", process::id()));
+        let request = operation::get_user(1)?.request;
+
+        let mut cache = Cache::with_clock(clock());
+        cache.dir(&dir);
+        cache.finish(&request, None, Fake::default().execute(&request))?;
+
+        let mut cache = Cache::with_clock(clock());
+        cache.dir(&dir);
+        let cached = match cache.lookup(&request) {
+            Lookup::Fresh(response) => response,
+            _ => panic!("response not cached on disk"),
+        };
+        assert_eq!(cached.body, b"1");
+        assert_eq!(cached.header("etag"), Some("\"v1\""));
+
+        let _ = fs::remove_dir_all(dir);
+
+        Ok(())
+    }
+}
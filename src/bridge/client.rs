@@ -0,0 +1,675 @@
+use crate::{builder::BotSearch, endpoints, model::*, Error, Result};
+use futures_util::stream::{self, StreamExt};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+use super::operation::{self, Operation, Request, Response};
+
+/// The most votes returned by `get_bot_votes`.
+const VOTES_LIMIT: usize = 1000;
+
+/// An HTTP library which can send requests synchronously.
+///
+/// Implementing this is all that's needed to use the API with a [`Client`].
+///
+/// [`Client`]: struct.Client.html
+pub trait Backend {
+    /// Sends a request, returning its response regardless of the status code.
+    fn execute(&self, request: &Request) -> Result<Response>;
+}
+
+impl<B: Backend + ?Sized> Backend for Arc<B> {
+    fn execute(&self, request: &Request) -> Result<Response> {
+        (**self).execute(request)
+    }
+}
+
+/// An HTTP library which can send requests asynchronously.
+///
+/// Implementing this is all that's needed to use the API with an
+/// [`AsyncClient`].
+///
+/// [`AsyncClient`]: struct.AsyncClient.html
+pub trait AsyncBackend {
+    /// Sends a request, returning its response regardless of the status code.
+    fn execute(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response>> + Send;
+}
+
+impl<B: AsyncBackend + ?Sized + Send + Sync> AsyncBackend for Arc<B> {
+    fn execute(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response>> + Send {
+        (**self).execute(request)
+    }
+}
+
+/// Struct which defines the methods necessary to interact with the service,
+/// sending requests with a [`Backend`].
+///
+/// [`Backend`]: trait.Backend.html
+#[derive(Clone, Debug)]
+pub struct Client<B> {
+    backend: B,
+    base: String,
+}
+
+impl<B: Backend> Client<B> {
+    /// Creates a new client sending requests with a backend.
+    pub fn new(backend: B) -> Self {
+        Self::with_base(backend, endpoints::BASE)
+    }
+
+    /// Creates a new client which sends requests to an API at another base
+    /// URL, such as `http://localhost:8080/api`.
+    pub fn with_base(backend: B, base: impl Into<String>) -> Self {
+        Self {
+            backend,
+            base: base.into().trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// Retrieves information about a bot.
+    pub fn get_bot(&self, user_id: u64) -> Result<Bot> {
+        self.run(operation::get_bot(user_id)?)
+    }
+
+    /// Retrieves a list of bots via a search.
+    pub fn get_bots<F>(&self, search: F) -> Result<SearchResponse<Bot>>
+        where F: FnOnce(BotSearch) -> BotSearch {
+        self.run(operation::get_bots(search(BotSearch::default()))?)
+    }
+
+    /// Retrieves information about many bots, sending up to `concurrency`
+    /// requests at a time from a pool of threads.
+    ///
+    /// The results are in the same order as the IDs. Requests are rate
+    /// limited if the backend is, such as a [`RateLimited`] backend shared by
+    /// the threads.
+    ///
+    /// Each bot takes its own request. The API's search can't filter by ID,
+    /// so there is no cheaper fallback to searching for them in batches.
+    ///
+    /// [`RateLimited`]: ratelimit/struct.RateLimited.html
+    pub fn get_bots_by_ids(
+        &self,
+        ids: &[u64],
+        concurrency: usize,
+    ) -> Vec<(u64, Result<Bot>)>
+        where B: Sync {
+        pool(ids, concurrency, |id| self.get_bot(id))
+    }
+
+    /// Retrieves information about a bot's specific stats.
+    pub fn get_bot_stats(&self, user_id: u64) -> Result<BotStats> {
+        self.run(operation::get_bot_stats(user_id)?)
+    }
+
+    /// Retrieve whether a user has upvoted a bot in the last 24 hours.
+    ///
+    /// You can use this if your bot has over 1000 votes.
+    pub fn get_bot_vote_check(
+        &self,
+        auth: impl AsRef<str>,
+        bot_id: u64,
+        user_id: u64,
+    ) -> Result<bool> {
+        self.run(operation::get_bot_vote_check(auth, bot_id, user_id)?)
+    }
+
+    /// Retrieves whether each of many users has upvoted a bot in the last 24
+    /// hours, sending up to `concurrency` requests at a time from a pool of
+    /// threads.
+    ///
+    /// If the bot has fewer than 1000 votes this month, users who haven't
+    /// voted this month are answered from a single [`get_bot_votes`] call,
+    /// and only the rest are checked individually. Requests are rate limited
+    /// if the backend is, such as a [`RateLimited`] backend.
+    ///
+    /// `progress` is called with the number of users answered so far and the
+    /// total number of users, once at the start and after each check.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the [`get_bot_votes`] call, unless the bot has
+    /// too many votes to list, or else the first error of any individual
+    /// check.
+    ///
+    /// [`RateLimited`]: ratelimit/struct.RateLimited.html
+    /// [`get_bot_votes`]: #method.get_bot_votes
+    pub fn get_bot_vote_checks(
+        &self,
+        auth: impl AsRef<str>,
+        bot_id: u64,
+        user_ids: &[u64],
+        concurrency: usize,
+        progress: impl Fn(usize, usize) + Sync,
+    ) -> Result<HashMap<u64, bool>>
+        where B: Sync {
+        let auth = auth.as_ref();
+        let votes = listed(self.get_bot_votes(auth, bot_id))?;
+        let (mut voted, unchecked) = unvoted(user_ids, votes);
+        let total = voted.len() + unchecked.len();
+        let answered = AtomicUsize::new(voted.len());
+        progress(voted.len(), total);
+
+        let checks = pool(&unchecked, concurrency, |user_id| {
+            let check = self.get_bot_vote_check(auth, bot_id, user_id);
+            progress(answered.fetch_add(1, Ordering::SeqCst) + 1, total);
+
+            check
+        });
+
+        for (user_id, check) in checks {
+            voted.insert(user_id, check?);
+        }
+
+        Ok(voted)
+    }
+
+    /// Retrieves information to see who has upvoted a bot.
+    ///
+    /// **Note**: If your bot has over 1000 votes per month, then this can not
+    /// be used. Webhooks must instead be used.
+    pub fn get_bot_votes(
+        &self,
+        auth: impl AsRef<str>,
+        bot_id: u64,
+    ) -> Result<BotVotes> {
+        self.run(operation::get_bot_votes(auth, bot_id)?)
+    }
+
+    /// Retrieves information about a user.
+    pub fn get_user(&self, user_id: u64) -> Result<User> {
+        self.run(operation::get_user(user_id)?)
+    }
+
+    /// Retrieves whether the weekend multiplier is in effect, during which
+    /// votes count as two.
+    pub fn get_weekend(&self, auth: impl AsRef<str>) -> Result<bool> {
+        self.run(operation::get_weekend(auth)?)
+    }
+
+    /// Posts a bot's shard stats.
+    pub fn post_stats(
+        &self,
+        auth: impl AsRef<str>,
+        bot_id: u64,
+        stats: &ShardStats,
+    ) -> Result<()> {
+        self.run(operation::post_stats(auth, bot_id, stats)?)
+    }
+
+    /// Sends an operation's request with the backend and parses its
+    /// response.
+    pub fn run<T>(&self, mut op: Operation<T>) -> Result<T> {
+        rebase(&mut op.request, &self.base);
+        let response = self.backend.execute(&op.request)?;
+
+        op.parse(response)
+    }
+}
+
+/// Struct which defines the methods necessary to interact with the service,
+/// sending requests with an [`AsyncBackend`].
+///
+/// This has the same methods as the [`Client`], returning futures instead.
+///
+/// [`AsyncBackend`]: trait.AsyncBackend.html
+/// [`Client`]: struct.Client.html
+#[derive(Clone, Debug)]
+pub struct AsyncClient<B> {
+    backend: B,
+    base: String,
+}
+
+impl<B: AsyncBackend> AsyncClient<B> {
+    /// Creates a new client sending requests with a backend.
+    pub fn new(backend: B) -> Self {
+        Self::with_base(backend, endpoints::BASE)
+    }
+
+    /// Creates a new client which sends requests to an API at another base
+    /// URL, such as `http://localhost:8080/api`.
+    pub fn with_base(backend: B, base: impl Into<String>) -> Self {
+        Self {
+            backend,
+            base: base.into().trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// Retrieves information about a bot.
+    pub async fn get_bot(&self, user_id: u64) -> Result<Bot> {
+        self.run(operation::get_bot(user_id)?).await
+    }
+
+    /// Retrieves a list of bots via a search.
+    pub async fn get_bots<F>(&self, search: F) -> Result<SearchResponse<Bot>>
+        where F: FnOnce(BotSearch) -> BotSearch {
+        self.run(operation::get_bots(search(BotSearch::default()))?).await
+    }
+
+    /// Retrieves information about many bots, sending up to `concurrency`
+    /// requests at a time.
+    ///
+    /// The results are in the same order as the IDs. Requests are rate
+    /// limited if the backend is, such as a [`RateLimited`] backend.
+    ///
+    /// Each bot takes its own request. The API's search can't filter by ID,
+    /// so there is no cheaper fallback to searching for them in batches.
+    ///
+    /// [`RateLimited`]: ratelimit/struct.RateLimited.html
+    pub async fn get_bots_by_ids(
+        &self,
+        ids: &[u64],
+        concurrency: usize,
+    ) -> Vec<(u64, Result<Bot>)> {
+        stream::iter(ids.iter().copied())
+            .map(|id| async move { (id, self.get_bot(id).await) })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Retrieves information about a bot's specific stats.
+    pub async fn get_bot_stats(&self, user_id: u64) -> Result<BotStats> {
+        self.run(operation::get_bot_stats(user_id)?).await
+    }
+
+    /// Retrieve whether a user has upvoted a bot in the last 24 hours.
+    ///
+    /// You can use this if your bot has over 1000 votes.
+    pub async fn get_bot_vote_check(
+        &self,
+        auth: impl AsRef<str>,
+        bot_id: u64,
+        user_id: u64,
+    ) -> Result<bool> {
+        self.run(operation::get_bot_vote_check(auth, bot_id, user_id)?).await
+    }
+
+    /// Retrieves whether each of many users has upvoted a bot in the last 24
+    /// hours, sending up to `concurrency` requests at a time.
+    ///
+    /// If the bot has fewer than 1000 votes this month, users who haven't
+    /// voted this month are answered from a single [`get_bot_votes`] call,
+    /// and only the rest are checked individually. Requests are rate limited
+    /// if the backend is, such as a [`RateLimited`] backend.
+    ///
+    /// `progress` is called with the number of users answered so far and the
+    /// total number of users, once at the start and after each check.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the [`get_bot_votes`] call, unless the bot has
+    /// too many votes to list, or else the first error of any individual
+    /// check.
+    ///
+    /// [`RateLimited`]: ratelimit/struct.RateLimited.html
+    /// [`get_bot_votes`]: #method.get_bot_votes
+    pub async fn get_bot_vote_checks(
+        &self,
+        auth: impl AsRef<str>,
+        bot_id: u64,
+        user_ids: &[u64],
+        concurrency: usize,
+        progress: impl Fn(usize, usize),
+    ) -> Result<HashMap<u64, bool>> {
+        let auth = auth.as_ref();
+        let votes = listed(self.get_bot_votes(auth, bot_id).await)?;
+        let (mut voted, unchecked) = unvoted(user_ids, votes);
+        let total = voted.len() + unchecked.len();
+        let answered = AtomicUsize::new(voted.len());
+        progress(voted.len(), total);
+
+        let mut checks = stream::iter(unchecked)
+            .map(|user_id| async move {
+                (user_id, self.get_bot_vote_check(auth, bot_id, user_id).await)
+            })
+            .buffered(concurrency.max(1));
+
+        while let Some((user_id, check)) = checks.next().await {
+            progress(answered.fetch_add(1, Ordering::SeqCst) + 1, total);
+            voted.insert(user_id, check?);
+        }
+
+        Ok(voted)
+    }
+
+    /// Retrieves information to see who has upvoted a bot.
+    ///
+    /// **Note**: If your bot has over 1000 votes per month, then this can not
+    /// be used. Webhooks must instead be used.
+    pub async fn get_bot_votes(
+        &self,
+        auth: impl AsRef<str>,
+        bot_id: u64,
+    ) -> Result<BotVotes> {
+        self.run(operation::get_bot_votes(auth, bot_id)?).await
+    }
+
+    /// Retrieves information about a user.
+    pub async fn get_user(&self, user_id: u64) -> Result<User> {
+        self.run(operation::get_user(user_id)?).await
+    }
+
+    /// Retrieves whether the weekend multiplier is in effect, during which
+    /// votes count as two.
+    pub async fn get_weekend(&self, auth: impl AsRef<str>) -> Result<bool> {
+        self.run(operation::get_weekend(auth)?).await
+    }
+
+    /// Posts a bot's shard stats.
+    pub async fn post_stats(
+        &self,
+        auth: impl AsRef<str>,
+        bot_id: u64,
+        stats: &ShardStats,
+    ) -> Result<()> {
+        self.run(operation::post_stats(auth, bot_id, stats)?).await
+    }
+
+    /// Sends an operation's request with the backend and parses its
+    /// response.
+    pub async fn run<T>(&self, mut op: Operation<T>) -> Result<T> {
+        rebase(&mut op.request, &self.base);
+        let response = self.backend.execute(&op.request).await?;
+
+        op.parse(response)
+    }
+}
+
+fn pool<T: Send>(
+    ids: &[u64],
+    concurrency: usize,
+    f: impl Fn(u64) -> T + Sync,
+) -> Vec<(u64, T)> {
+    let next = AtomicUsize::new(0);
+    let workers = concurrency.clamp(1, ids.len().max(1));
+
+    let mut results = thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let id = match ids.get(index) {
+                            Some(id) => *id,
+                            None => break results,
+                        };
+
+                        results.push((index, id, f(id)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle.join().unwrap_or_else(|p| panic::resume_unwind(p))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_by_key(|(index, ..)| *index);
+    results.into_iter().map(|(_, id, result)| (id, result)).collect()
+}
+
+fn rebase(request: &mut Request, base: &str) {
+    if let Some(path) = request.url.strip_prefix(endpoints::BASE) {
+        request.url = format!("{}{}", base, path);
+    }
+}
+
+/// Discards the month's votes if the API refuses to list them, which it does
+/// with a 403 Forbidden for bots with too many votes.
+fn listed(votes: Result<BotVotes>) -> Result<Option<BotVotes>> {
+    match votes {
+        Ok(votes) => Ok(Some(votes)),
+        Err(Error::Status(ref response)) if response.status == 403 => Ok(None),
+        Err(why) => Err(why),
+    }
+}
+
+/// Splits users into those known not to have voted, and the rest.
+///
+/// A complete list of the month's voters shows who hasn't voted in the last
+/// 24 hours, but not who has.
+fn unvoted(
+    user_ids: &[u64],
+    votes: Option<BotVotes>,
+) -> (HashMap<u64, bool>, Vec<u64>) {
+    let voters = votes
+        .map(|votes| match votes {
+            BotVotes::Ids(ids) => ids,
+            BotVotes::Users(users) => {
+                users.iter().filter_map(|user| user.id.parse().ok()).collect()
+            },
+        })
+        .filter(|voters| voters.len() < VOTES_LIMIT)
+        .map(|voters| voters.into_iter().collect::<HashSet<u64>>());
+    let mut seen = HashSet::new();
+    let mut unvoted = HashMap::new();
+    let mut unchecked = Vec::new();
+
+    for user_id in user_ids {
+        if !seen.insert(*user_id) {
+            continue;
+        }
+
+        match &voters {
+            Some(voters) if !voters.contains(user_id) => {
+                unvoted.insert(*user_id, false);
+            },
+            _ => unchecked.push(*user_id),
+        }
+    }
+
+    (unvoted, unchecked)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{model::Bot, route::Route, Error, Result};
+    use serde_json::json;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+    use super::{
+        operation::{Request, Response},
+        AsyncBackend,
+        AsyncClient,
+        Backend,
+        Client,
+    };
+
+    /// A backend with every bot except 404.
+    ///
+    /// Users 2 and 4 have voted for bot 1 this month, and user 2 has voted
+    /// for every bot today. Bots other than 1 have too many votes to list.
+    #[derive(Debug, Default)]
+    struct Fake {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        requests: AtomicUsize,
+    }
+
+    impl Fake {
+        fn respond(&self, request: &Request) -> Response {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let body = match &request.route {
+                Route::GetBot { id: 404 } => {
+                    return Response {
+                        body: Vec::new(),
+                        headers: Vec::new(),
+                        status: 404,
+                    };
+                },
+                Route::GetBot { id } => bot(*id),
+                Route::GetBotVoteCheck { user_id, .. } => {
+                    json!({ "voted": (*user_id == 2) as u8 })
+                },
+                Route::GetBotVotes { id: 1 } => json!([2, 4]),
+                Route::GetBotVotes { id: 401 } => {
+                    return Response {
+                        body: Vec::new(),
+                        headers: Vec::new(),
+                        status: 401,
+                    };
+                },
+                Route::GetBotVotes { .. } => {
+                    return Response {
+                        body: Vec::new(),
+                        headers: Vec::new(),
+                        status: 403,
+                    };
+                },
+                _ => unreachable!(),
+            };
+
+            Response {
+                body: body.to_string().into_bytes(),
+                headers: Vec::new(),
+                status: 200,
+            }
+        }
+    }
+
+    impl Backend for Fake {
+        fn execute(&self, request: &Request) -> Result<Response> {
+            Ok(self.respond(request))
+        }
+    }
+
+    impl AsyncBackend for Fake {
+        async fn execute(&self, request: &Request) -> Result<Response> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(self.respond(request))
+        }
+    }
+
+    fn bot(id: u64) -> serde_json::Value {
+        json!({
+            "avatar": null,
+            "certifiedBot": false,
+            "date": "2019-01-01T00:00:00.000Z",
+            "defAvatar": null,
+            "discriminator": "0001",
+            "id": id.to_string(),
+            "lib": "serenity",
+            "owners": ["2"],
+            "points": 10,
+            "prefix": "!",
+            "shortdesc": "A bot.",
+            "tags": [],
+            "username": format!("Bot {}", id),
+        })
+    }
+
+    fn ids(results: &[(u64, Result<Bot>)]) -> Vec<String> {
+        results
+            .iter()
+            .map(|(id, bot)| match bot {
+                Ok(bot) => format!("{}={}", id, bot.id),
+                Err(Error::Status(resp)) => format!("{}={}", id, resp.status),
+                Err(why) => panic!("unexpected error: {}", why),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_get_bots_by_ids() {
+        let client = Client::new(Fake::default());
+        let results = client.get_bots_by_ids(&[3, 1, 404, 2], 3);
+
+        assert_eq!(ids(&results), ["3=3", "1=1", "404=404", "2=2"]);
+        assert!(client.get_bots_by_ids(&[], 3).is_empty());
+        assert_eq!(client.backend.requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_get_bots_by_ids_async() {
+        let client = AsyncClient::new(Fake::default());
+        let ids_in = (1..=20).collect::<Vec<_>>();
+        let results = client.get_bots_by_ids(&ids_in, 4).await;
+
+        let expected = ids_in
+            .iter()
+            .map(|id| format!("{}={}", id, id))
+            .collect::<Vec<_>>();
+        assert_eq!(ids(&results), expected);
+        assert_eq!(client.backend.max_in_flight.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_get_bot_vote_checks() -> Result<()> {
+        let client = Client::new(Fake::default());
+        let progress = Mutex::new(Vec::new());
+        let report = |done, total| progress.lock().unwrap().push((done, total));
+        let users = [1, 2, 3, 4, 2];
+        let voted = client.get_bot_vote_checks("a", 1, &users, 2, report)?;
+
+        let expected = [(1, false), (2, true), (3, false), (4, false)];
+        assert_eq!(voted, expected.iter().copied().collect());
+        // Only the month's voters are checked individually.
+        assert_eq!(client.backend.requests.load(Ordering::SeqCst), 3);
+
+        let mut progress = progress.into_inner().unwrap();
+        progress.sort_unstable();
+        assert_eq!(progress, [(2, 4), (3, 4), (4, 4)]);
+
+        let client = Client::new(Fake::default());
+        let voted =
+            client.get_bot_vote_checks("a", 2, &[1, 2, 3], 2, |_, _| {})?;
+        assert_eq!(voted.values().filter(|voted| **voted).count(), 1);
+        assert_eq!(client.backend.requests.load(Ordering::SeqCst), 4);
+
+        // Errors other than too many votes aren't worked around.
+        let client = Client::new(Fake::default());
+        let voted = client.get_bot_vote_checks("a", 401, &[1, 2], 2, |_, _| {});
+        match voted {
+            Err(Error::Status(response)) => assert_eq!(response.status, 401),
+            other => panic!("expected a 401, got {:?}", other),
+        }
+        assert_eq!(client.backend.requests.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_bot_vote_checks_async() -> Result<()> {
+        let client = AsyncClient::new(Fake::default());
+        let calls = AtomicUsize::new(0);
+        let report = |_, _| {
+            calls.fetch_add(1, Ordering::SeqCst);
+        };
+        let voted = client.get_bot_vote_checks("a", 1, &[1, 2, 4], 2, report);
+        let voted = voted.await?;
+
+        assert_eq!(voted.get(&1), Some(&false));
+        assert_eq!(voted.get(&2), Some(&true));
+        assert_eq!(voted.get(&4), Some(&false));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+//! Bridge to provide a client implementation for the `hyper` crate.
+//!
+//! # Examples
+//!
+//! Refer to the documentation for [`Client`].
+//!
+//! [`Client`]: type.Client.html
+
+use bytes::Bytes;
+use crate::{
+    bridge::{
+        operation::{Request, Response},
+        AsyncBackend,
+        AsyncClient,
+    },
+    Result,
+};
+use http::Request as HttpRequest;
+use http_body_util::{BodyExt, Full};
+use hyper_util::client::legacy::{connect::Connect, Client as HyperClient};
+use std::future::Future;
+
+/// Struct which defines the methods necessary to interact with the service.
+///
+/// This is a [`bridge::AsyncClient`] on a hyper client with any connector,
+/// such as one supporting TLS. The hyper client is cheap to clone, so a
+/// single connection pool may be shared across your application.
+///
+/// # Examples
+///
+/// Request a bot by ID:
+///
+/// ```rust,no_run
+/// use discord_bots_org::bridge::hyper::Client as ApiClient;
+/// use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+///
+/// #[tokio::main]
+/// async fn main() -> discord_bots_org::Result<()> {
+///     let hyper_client = Client::builder(TokioExecutor::new()).build_http();
+///     let client = ApiClient::new(hyper_client);
+///     let bot = client.get_bot(270_198_738_570_444_801).await?;
+///
+///     println!("The bot's name is: {}", bot.username);
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [`bridge::AsyncClient`]: ../struct.AsyncClient.html
+pub type Client<C> = AsyncClient<HyperClient<C, Full<Bytes>>>;
+
+impl<C> AsyncBackend for HyperClient<C, Full<Bytes>>
+    where C: Connect + Clone + Send + Sync + 'static {
+    fn execute(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response>> + Send {
+        let mut builder = HttpRequest::builder()
+            .method(request.method.as_str())
+            .uri(request.url.as_str());
+
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value.as_str());
+        }
+
+        let body = request.body.clone().map(Full::from).unwrap_or_default();
+        let request = builder.body(body);
+        let client = self.clone();
+
+        async move {
+            let resp = client.request(request?).await?;
+            let headers = resp
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_owned()))
+                })
+                .collect();
+            let status = resp.status().as_u16();
+            let body = resp.into_body().collect().await?.to_bytes();
+
+            Ok(Response {
+                body: body.to_vec(),
+                headers,
+                status,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crate::{mock::MockServer, model::ShardStats, route::Route, Error};
+    use http_body_util::Full;
+    use hyper_util::{
+        client::legacy::{connect::HttpConnector, Client as HyperClient},
+        rt::TokioExecutor,
+    };
+    use super::Client;
+
+    const STATS: &str = r#"{"serverCount":5,"shards":[]}"#;
+
+    fn client(server: &MockServer) -> Client<HttpConnector> {
+        let hyper = HyperClient::builder(TokioExecutor::new())
+            .build::<_, Full<Bytes>>(HttpConnector::new());
+
+        Client::with_base(hyper, server.url())
+    }
+
+    #[tokio::test]
+    async fn test_get() {
+        let server = MockServer::start(&[
+            (Route::GetBotStats { id: 1 }, 200, STATS),
+            (
+                Route::GetBotVoteCheck { bot_id: 1, user_id: 2 },
+                200,
+                r#"{"voted":1}"#,
+            ),
+        ]);
+        let client = client(&server);
+
+        let stats = client.get_bot_stats(1).await.unwrap();
+        assert_eq!(stats.server_count, Some(5));
+        assert!(client.get_bot_vote_check("auth", 1, 2).await.unwrap());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].authorization, None);
+        assert_eq!(requests[1].authorization.as_deref(), Some("auth"));
+        assert_eq!(requests[1].path, "/bots/1/check?userId=2");
+    }
+
+    #[tokio::test]
+    async fn test_post_stats() {
+        let route = Route::PostBotStats { id: 1 };
+        let server = MockServer::start(&[(route, 200, "{}")]);
+        let stats = ShardStats::Shards(vec![1, 2]);
+
+        client(&server).post_stats("auth", 1, &stats).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].authorization.as_deref(), Some("auth"));
+        let body = serde_json::from_str::<Vec<u64>>(&requests[0].body);
+        assert_eq!(body.unwrap(), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_status_error() {
+        let server = MockServer::start(&[]);
+
+        match client(&server).get_user(1).await {
+            Err(Error::Status(resp)) => assert_eq!(resp.status, 404),
+            other => panic!("expected a 404, got {:?}", other),
+        }
+    }
+}
@@ -1,4 +1,28 @@
 //! Bridging support between the library and various HTTP clients.
+//!
+//! The API's operations are described by the [`operation`] module, and sent
+//! by a [`Client`] or [`AsyncClient`] with any [`Backend`] or
+//! [`AsyncBackend`]. Backends are implemented for the supported HTTP
+//! libraries, and other libraries can be supported by implementing one of
+//! the traits.
+//!
+//! [`AsyncBackend`]: trait.AsyncBackend.html
+//! [`AsyncClient`]: struct.AsyncClient.html
+//! [`Backend`]: trait.Backend.html
+//! [`Client`]: struct.Client.html
+//! [`operation`]: operation/index.html
 
+pub mod cache;
+pub mod operation;
+pub mod ratelimit;
+
+#[cfg(feature = "hyper-support")]
+pub mod hyper;
 #[cfg(any(feature = "reqwest-sync-support", feature = "reqwest-async-support"))]
 pub mod reqwest;
+#[cfg(feature = "ureq-support")]
+pub mod ureq;
+
+mod client;
+
+pub use self::client::{AsyncBackend, AsyncClient, Backend, Client};
@@ -0,0 +1,252 @@
+//! Backend-neutral descriptions of the API's operations.
+//!
+//! Each function creates an [`Operation`], which is made of the [`Request`]
+//! to send and a parser for its [`Response`]. A [`Backend`] only needs to
+//! send requests, so all clients behave the same.
+//!
+//! [`Backend`]: ../trait.Backend.html
+//! [`Operation`]: struct.Operation.html
+//! [`Request`]: struct.Request.html
+//! [`Response`]: struct.Response.html
+
+use crate::{
+    builder::BotSearch,
+    model::*,
+    route::Route,
+    Error,
+    Result,
+};
+use serde::de::DeserializeOwned;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+pub use crate::route::Method;
+
+/// An HTTP request to send to the API.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Request {
+    /// The body of the request, if any.
+    pub body: Option<Vec<u8>>,
+    /// The names and values of the request's headers.
+    pub headers: Vec<(&'static str, String)>,
+    /// The method of the request.
+    pub method: Method,
+    /// The route of the request.
+    pub route: Route,
+    /// The URL of the request, including its query.
+    pub url: String,
+}
+
+impl Request {
+    /// Creates a request to a route, without a body or headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidUrl`] if the route's URL could not be parsed.
+    ///
+    /// [`Error::InvalidUrl`]: ../../enum.Error.html#variant.InvalidUrl
+    pub fn new(route: Route) -> Result<Self> {
+        Ok(Self {
+            body: None,
+            headers: Vec::new(),
+            method: route.method(),
+            url: route.url()?.into(),
+            route,
+        })
+    }
+
+    /// Adds an `Authorization` header with an API token to the request.
+    pub fn auth(mut self, auth: impl AsRef<str>) -> Self {
+        self.headers.push(("Authorization", auth.as_ref().to_owned()));
+
+        self
+    }
+}
+
+/// An HTTP response received from the API.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Response {
+    /// The body of the response.
+    pub body: Vec<u8>,
+    /// The names and values of the response's headers.
+    pub headers: Vec<(String, String)>,
+    /// The status code of the response.
+    pub status: u16,
+}
+
+impl Response {
+    /// Retrieves the value of a header, with a case-insensitive name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A request to the API, along with how to parse its response.
+pub struct Operation<T> {
+    parse: fn(&[u8]) -> Result<T>,
+    /// The request to send.
+    pub request: Request,
+}
+
+impl<T> Operation<T> {
+    /// Parses the response to the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Status`] if the response has an unsuccessful status
+    /// code, or [`Error::Json`] if its body could not be deserialized.
+    ///
+    /// [`Error::Json`]: ../../enum.Error.html#variant.Json
+    /// [`Error::Status`]: ../../enum.Error.html#variant.Status
+    pub fn parse(&self, response: Response) -> Result<T> {
+        if !(200..300).contains(&response.status) {
+            return Err(Error::Status(response));
+        }
+
+        (self.parse)(&response.body)
+    }
+}
+
+impl<T> Debug for Operation<T> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("Operation")
+            .field("request", &self.request)
+            .finish()
+    }
+}
+
+/// Retrieves information about a bot.
+pub fn get_bot(user_id: u64) -> Result<Operation<Bot>> {
+    let request = Request::new(Route::GetBot { id: user_id })?;
+
+    Ok(json(request))
+}
+
+/// Retrieves a list of bots via a search.
+pub fn get_bots(search: BotSearch) -> Result<Operation<SearchResponse<Bot>>> {
+    let request = Request::new(Route::GetBots { params: search.build() })?;
+
+    Ok(json(request))
+}
+
+/// Retrieves information about a bot's specific stats.
+pub fn get_bot_stats(user_id: u64) -> Result<Operation<BotStats>> {
+    let request = Request::new(Route::GetBotStats { id: user_id })?;
+
+    Ok(json(request))
+}
+
+/// Retrieve whether a user has upvoted a bot in the last 24 hours.
+pub fn get_bot_vote_check(
+    auth: impl AsRef<str>,
+    bot_id: u64,
+    user_id: u64,
+) -> Result<Operation<bool>> {
+    let route = Route::GetBotVoteCheck { bot_id, user_id };
+
+    Ok(Operation {
+        parse: |body| {
+            let resp = serde_json::from_slice::<ResponseUserVoted>(body)?;
+
+            Ok(resp.voted == 1)
+        },
+        request: Request::new(route)?.auth(auth),
+    })
+}
+
+/// Retrieves information to see who has upvoted a bot.
+pub fn get_bot_votes(
+    auth: impl AsRef<str>,
+    bot_id: u64,
+) -> Result<Operation<BotVotes>> {
+    let request = Request::new(Route::GetBotVotes { id: bot_id })?;
+
+    Ok(json(request.auth(auth)))
+}
+
+/// Retrieves information about a user.
+pub fn get_user(user_id: u64) -> Result<Operation<User>> {
+    let request = Request::new(Route::GetUser { id: user_id })?;
+
+    Ok(json(request))
+}
+
+/// Retrieves whether the weekend multiplier is in effect.
+pub fn get_weekend(auth: impl AsRef<str>) -> Result<Operation<bool>> {
+    Ok(Operation {
+        parse: |body| {
+            let resp = serde_json::from_slice::<ResponseWeekend>(body)?;
+
+            Ok(resp.is_weekend)
+        },
+        request: Request::new(Route::GetWeekend)?.auth(auth),
+    })
+}
+
+/// Posts a bot's shard stats.
+pub fn post_stats(
+    auth: impl AsRef<str>,
+    bot_id: u64,
+    stats: &ShardStats,
+) -> Result<Operation<()>> {
+    let mut request = Request::new(Route::PostBotStats { id: bot_id })?;
+    request.body = Some(serde_json::to_vec(stats)?);
+    request.headers.push(("Content-Type", "application/json".to_owned()));
+
+    Ok(Operation {
+        parse: |_| Ok(()),
+        request: request.auth(auth),
+    })
+}
+
+fn json<T: DeserializeOwned>(request: Request) -> Operation<T> {
+    Operation {
+        parse: |body| serde_json::from_slice(body).map_err(From::from),
+        request,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{model::ShardStats, Error};
+    use super::{Method, Response};
+
+    #[test]
+    fn test_request() {
+        let op = super::get_bot_votes("auth", 1).unwrap();
+        assert_eq!(op.request.method, Method::Get);
+        assert_eq!(op.request.url, "https://discordbots.org/api/bots/1/votes");
+        assert!(op.request.route.requires_auth());
+        assert_eq!(op.request.headers, vec![("Authorization", "auth".into())]);
+
+        let stats = ShardStats::Shards(vec![1, 2]);
+        let op = super::post_stats("auth", 1, &stats).unwrap();
+        assert_eq!(op.request.method, Method::Post);
+        assert_eq!(op.request.body.as_deref(), Some(&b"[1,2]"[..]));
+    }
+
+    #[test]
+    fn test_parse() {
+        let op = super::get_bot_vote_check("auth", 1, 2).unwrap();
+        assert_eq!(
+            op.request.url,
+            "https://discordbots.org/api/bots/1/check?userId=2",
+        );
+
+        let response = |status, body: &str| Response {
+            body: body.as_bytes().to_vec(),
+            headers: Vec::new(),
+            status,
+        };
+
+        assert!(op.parse(response(200, r#"{"voted":1}"#)).unwrap());
+        assert!(!op.parse(response(200, r#"{"voted":0}"#)).unwrap());
+        assert!(matches!(
+            op.parse(response(401, r#"{"voted":1}"#)),
+            Err(Error::Status(Response { status: 401, .. })),
+        ));
+        assert!(matches!(op.parse(response(200, "")), Err(Error::Json(_))));
+    }
+}
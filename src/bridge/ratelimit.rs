@@ -0,0 +1,518 @@
+//! Rate limiting of requests by the [bucket] of their route.
+//!
+//! # Examples
+//!
+//! Refer to the documentation for [`RateLimited`].
+//!
+//! [`RateLimited`]: struct.RateLimited.html
+//! [bucket]: ../../route/enum.Bucket.html
+
+use chrono::{DateTime, Duration, Utc};
+use crate::{
+    route::{Bucket, Route},
+    vote::{Clock, SystemClock},
+    Result,
+};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard, Once},
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration as StdDuration, Instant},
+};
+use super::{
+    operation::{Request, Response},
+    AsyncBackend,
+    Backend,
+};
+
+/// A limiter of requests to the API, keeping within the limit of each
+/// [bucket].
+///
+/// Every request counts against the global bucket, as well as the bucket of
+/// its route.
+///
+/// [bucket]: ../../route/enum.Bucket.html
+#[derive(Debug)]
+pub struct RateLimiter<C = SystemClock> {
+    clock: C,
+    windows: Mutex<HashMap<Bucket, Window>>,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<C: Clock> RateLimiter<C> {
+    /// Creates a new rate limiter which uses the given clock.
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            clock,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Blocks requests to a bucket for a duration, such as after a response
+    /// with a `Retry-After` header.
+    pub fn block(&self, bucket: Bucket, duration: Duration) {
+        let until = self.clock.now() + duration;
+        let mut windows = self.lock();
+        let window = windows.entry(bucket).or_default();
+
+        window.blocked_until = window.blocked_until.max(Some(until));
+    }
+
+    /// Reserves a slot for a request to a route, returning how long to wait
+    /// before sending it.
+    pub fn reserve(&self, route: &Route) -> Duration {
+        let now = self.clock.now();
+        let buckets = match route.bucket() {
+            Bucket::Global => vec![Bucket::Global],
+            bucket => vec![bucket, Bucket::Global],
+        };
+        let mut windows = self.lock();
+
+        let at = buckets
+            .iter()
+            .map(|b| windows.entry(*b).or_default().next(*b, now))
+            .max()
+            .unwrap_or(now);
+
+        for bucket in &buckets {
+            windows.entry(*bucket).or_default().insert(at);
+        }
+
+        at - now
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Bucket, Window>> {
+        self.windows.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A backend which waits for a [`RateLimiter`] before sending requests with
+/// another backend.
+///
+/// This implements [`Backend`] when the inner backend does, and
+/// [`AsyncBackend`] when the inner backend does. Async requests wait on a
+/// single timer thread shared by all limiters, so they work with any async
+/// runtime.
+///
+/// Responses with a 429 status code and a `Retry-After` header block the
+/// route's bucket for that long. Clones of the backend share the same
+/// limiter.
+///
+/// # Examples
+///
+/// Share one rate limiter between threads:
+///
+/// ```rust,no_run
+/// use discord_bots_org::bridge::{
+///     ratelimit::{RateLimited, RateLimiter},
+///     Client,
+/// };
+/// use reqwest::blocking::Client as ReqwestClient;
+/// use std::thread;
+///
+/// let backend = RateLimited::new(ReqwestClient::new(), RateLimiter::new());
+///
+/// for id in 1..=4 {
+///     let client = Client::new(backend.clone());
+///
+///     thread::spawn(move || client.get_bot_stats(id));
+/// }
+/// ```
+///
+/// [`AsyncBackend`]: ../trait.AsyncBackend.html
+/// [`Backend`]: ../trait.Backend.html
+/// [`RateLimiter`]: struct.RateLimiter.html
+#[derive(Debug)]
+pub struct RateLimited<B, C = SystemClock> {
+    inner: Arc<B>,
+    limiter: Arc<RateLimiter<C>>,
+}
+
+impl<B, C> RateLimited<B, C> {
+    /// Creates a new backend rate limiting requests sent by another.
+    pub fn new(inner: B, limiter: RateLimiter<C>) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            limiter: Arc::new(limiter),
+        }
+    }
+
+    /// The rate limiter of the backend.
+    pub fn limiter(&self) -> &RateLimiter<C> {
+        &self.limiter
+    }
+}
+
+impl<B, C: Clock> RateLimited<B, C> {
+    fn received(&self, request: &Request, response: &Response) {
+        if response.status != 429 {
+            return;
+        }
+
+        let retry_after = response
+            .header("retry-after")
+            .and_then(|secs| secs.trim().parse::<f64>().ok())
+            .filter(|secs| secs.is_finite() && *secs > 0.0);
+
+        if let Some(secs) = retry_after {
+            let duration = Duration::milliseconds((secs * 1000.0) as i64);
+            self.limiter.block(request.route.bucket(), duration);
+        }
+    }
+}
+
+impl<B, C> Clone for RateLimited<B, C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            limiter: Arc::clone(&self.limiter),
+        }
+    }
+}
+
+impl<B: Backend, C: Clock> Backend for RateLimited<B, C> {
+    fn execute(&self, request: &Request) -> Result<Response> {
+        let wait = self.limiter.reserve(&request.route);
+
+        if let Ok(wait) = wait.to_std() {
+            thread::sleep(wait);
+        }
+
+        let response = self.inner.execute(request)?;
+        self.received(request, &response);
+
+        Ok(response)
+    }
+}
+
+impl<B, C> AsyncBackend for RateLimited<B, C>
+where
+    B: AsyncBackend + Send + Sync,
+    C: Clock + Send + Sync,
+{
+    fn execute(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response>> + Send {
+        let wait = self.limiter.reserve(&request.route);
+
+        async move {
+            if let Ok(wait) = wait.to_std() {
+                if !wait.is_zero() {
+                    Sleep::new(wait).await;
+                }
+            }
+
+            let response = self.inner.execute(request).await?;
+            self.received(request, &response);
+
+            Ok(response)
+        }
+    }
+}
+
+/// The timer which wakes the sleeping requests of every rate limiter.
+static TIMER: Timer = Timer {
+    changed: Condvar::new(),
+    deadlines: Mutex::new(BinaryHeap::new()),
+    started: Once::new(),
+};
+
+/// A future which completes after a duration, waking on the shared timer.
+struct Sleep {
+    at: Instant,
+    waker: Option<Arc<Mutex<Option<Waker>>>>,
+}
+
+impl Sleep {
+    fn new(duration: StdDuration) -> Self {
+        Self {
+            at: Instant::now() + duration,
+            waker: None,
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        match &self.waker {
+            Some(waker) => *lock(waker) = Some(cx.waker().clone()),
+            None => {
+                let waker = Arc::new(Mutex::new(Some(cx.waker().clone())));
+                TIMER.add(Deadline {
+                    at: self.at,
+                    waker: Arc::clone(&waker),
+                });
+                self.waker = Some(waker);
+            },
+        }
+
+        // Checked after storing the waker, so that a deadline which passed
+        // in between isn't missed.
+        if Instant::now() >= self.at {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A single thread waking sleeping requests at their deadlines, so waiting
+/// requests don't each need a thread.
+struct Timer {
+    changed: Condvar,
+    deadlines: Mutex<BinaryHeap<Reverse<Deadline>>>,
+    started: Once,
+}
+
+impl Timer {
+    fn add(&'static self, deadline: Deadline) {
+        self.started.call_once(|| {
+            thread::Builder::new()
+                .name("discord-bots-org-timer".to_owned())
+                .spawn(move || self.run())
+                .expect("failed to spawn the rate limit timer thread");
+        });
+
+        lock(&self.deadlines).push(Reverse(deadline));
+        self.changed.notify_one();
+    }
+
+    fn run(&self) {
+        let mut deadlines = lock(&self.deadlines);
+
+        loop {
+            let now = Instant::now();
+            let mut due = Vec::new();
+
+            while deadlines.peek().is_some_and(|next| next.0.at <= now) {
+                due.extend(deadlines.pop());
+            }
+
+            // Wakers are called without the lock held, as they may poll the
+            // future and add another deadline.
+            if !due.is_empty() {
+                drop(deadlines);
+
+                for Reverse(deadline) in due {
+                    if let Some(waker) = lock(&deadline.waker).take() {
+                        waker.wake();
+                    }
+                }
+
+                deadlines = lock(&self.deadlines);
+
+                continue;
+            }
+
+            deadlines = match deadlines.peek() {
+                Some(next) => {
+                    let wait = next.0.at.saturating_duration_since(now);
+
+                    self.changed
+                        .wait_timeout(deadlines, wait)
+                        .unwrap_or_else(|p| p.into_inner())
+                        .0
+                },
+                None => self
+                    .changed
+                    .wait(deadlines)
+                    .unwrap_or_else(|p| p.into_inner()),
+            };
+        }
+    }
+}
+
+/// When to wake a sleeping request.
+struct Deadline {
+    at: Instant,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl Eq for Deadline {}
+
+impl Ord for Deadline {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.at.cmp(&other.at)
+    }
+}
+
+impl PartialEq for Deadline {
+    fn eq(&self, other: &Self) -> bool {
+        self.at == other.at
+    }
+}
+
+impl PartialOrd for Deadline {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    blocked_until: Option<DateTime<Utc>>,
+    sent: VecDeque<DateTime<Utc>>,
+}
+
+impl Window {
+    fn insert(&mut self, at: DateTime<Utc>) {
+        let index = self.sent.partition_point(|sent| *sent <= at);
+        self.sent.insert(index, at);
+    }
+
+    fn next(&mut self, bucket: Bucket, now: DateTime<Utc>) -> DateTime<Utc> {
+        let (limit, window) = bucket.limit();
+
+        while self.sent.front().is_some_and(|sent| *sent <= now - window) {
+            self.sent.pop_front();
+        }
+
+        let mut at = now.max(self.blocked_until.unwrap_or(now));
+
+        if self.sent.len() >= limit {
+            at = at.max(self.sent[self.sent.len() - limit] + window);
+        }
+
+        at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use crate::{
+        bridge::{
+            operation::{self, Request, Response},
+            AsyncBackend,
+            Backend,
+        },
+        route::{Bucket, Route},
+        vote::ManualClock,
+        Result,
+    };
+    use std::time::{Duration as StdDuration, Instant};
+    use super::{RateLimited, RateLimiter, Sleep};
+
+    #[test]
+    fn test_reserve() {
+        let start = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let limiter = RateLimiter::with_clock(clock.clone());
+        let bot = Route::GetBot { id: 1 };
+        let user = Route::GetUser { id: 1 };
+
+        for _ in 0..60 {
+            assert_eq!(limiter.reserve(&bot), Duration::zero());
+        }
+
+        // Requests to bots also count against the global bucket.
+        for _ in 0..40 {
+            assert_eq!(limiter.reserve(&user), Duration::zero());
+        }
+
+        assert_eq!(limiter.reserve(&user), Duration::seconds(1));
+        assert_eq!(limiter.reserve(&bot), Duration::minutes(1));
+
+        clock.advance(Duration::seconds(2));
+        assert_eq!(limiter.reserve(&user), Duration::zero());
+
+        limiter.block(Bucket::Global, Duration::seconds(5));
+        assert_eq!(limiter.reserve(&user), Duration::seconds(5));
+    }
+
+    #[test]
+    fn test_retry_after() -> Result<()> {
+        struct Limited;
+
+        impl Backend for Limited {
+            fn execute(&self, _: &Request) -> Result<Response> {
+                Ok(Response {
+                    body: Vec::new(),
+                    headers: vec![("Retry-After".into(), "30".into())],
+                    status: 429,
+                })
+            }
+        }
+
+        let start = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+        let limiter = RateLimiter::with_clock(ManualClock::new(start));
+        let backend = RateLimited::new(Limited, limiter);
+        let request = operation::get_bot(1)?.request;
+
+        assert_eq!(backend.execute(&request)?.status, 429);
+        assert_eq!(
+            backend.limiter().reserve(&request.route),
+            Duration::seconds(30),
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_async_wait() -> Result<()> {
+        struct Ok200;
+
+        impl AsyncBackend for Ok200 {
+            async fn execute(&self, _: &Request) -> Result<Response> {
+                Ok(Response {
+                    body: Vec::new(),
+                    headers: Vec::new(),
+                    status: 200,
+                })
+            }
+        }
+
+        let backend = RateLimited::new(Ok200, RateLimiter::new());
+        let request = operation::get_bot(1)?.request;
+        backend.limiter().block(Bucket::Bots, Duration::milliseconds(50));
+
+        let start = Instant::now();
+        let response = AsyncBackend::execute(&backend, &request).await?;
+        assert_eq!(response.status, 200);
+        assert!(start.elapsed() >= StdDuration::from_millis(50));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sleep() {
+        let start = Instant::now();
+        let sleeps = [30, 10, 20, 10].map(|ms| async move {
+            Sleep::new(StdDuration::from_millis(ms)).await;
+
+            start.elapsed()
+        });
+        let [a, b, c, d] = sleeps;
+
+        // Sleeps sharing the timer each wake at their own deadline.
+        let elapsed = tokio::join!(a, b, c, d);
+        assert!(elapsed.0 >= StdDuration::from_millis(30));
+        assert!(elapsed.1 >= StdDuration::from_millis(10));
+        assert!(elapsed.1 < elapsed.0);
+        assert!(elapsed.2 >= StdDuration::from_millis(20));
+        assert!(elapsed.3 >= StdDuration::from_millis(10));
+    }
+}
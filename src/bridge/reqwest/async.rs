@@ -2,145 +2,177 @@
 //!
 //! # Examples
 //!
-//! Refer to the documentation for [`Requester`].
+//! Refer to the documentation for [`Client`].
 //!
-//! [`Requester`]: trait.Requester.html
+//! [`Client`]: type.Client.html
 
 use crate::{
-    endpoints,
-    model::*,
+    bridge::{
+        operation::{Request, Response},
+        AsyncBackend,
+        AsyncClient,
+    },
     Result,
 };
-use futures::compat::Future01CompatExt;
-use reqwest::{
-    r#async::Client as ReqwestClient,
-    header::{AUTHORIZATION, HeaderValue},
-    Url,
+use reqwest::{header::HeaderValue, Client as ReqwestClient};
+use std::{
+    future::{self, Future},
+    panic,
+    sync::{Arc, OnceLock},
+    thread,
+};
+use tokio::{
+    runtime::{Builder, Handle},
+    task::AbortHandle,
 };
-use serde::de::DeserializeOwned;
-use std::sync::Arc;
 
 /// Struct which defines the methods necessary to interact with the service.
 ///
+/// This is a [`bridge::AsyncClient`] on a reqwest Client, with the same
+/// methods as the [`ReqwestSyncClient`]. The futures work with any executor:
+/// reqwest requires a Tokio runtime, so when they aren't polled within one,
+/// requests are sent on a background runtime started on first use.
+///
+/// The background runtime is shared by the whole process and runs on its own
+/// thread until the process exits; it can't be shut down. A reqwest client's
+/// pooled connections belong to the runtime which opened them, so a client
+/// shouldn't be used both within a Tokio runtime and outside of one, and
+/// shouldn't be used after the runtime it was used within is dropped. Create
+/// a separate reqwest client for each runtime instead.
+///
 /// # Examples
 ///
-/// To bring in the API client:
+/// Request a bot by ID:
 ///
 /// ```rust,no_run
-/// use discord_bots_org::bridge::reqwest::r#async::Client;
+/// use discord_bots_org::ReqwestAsyncClient as ApiClient;
+/// use reqwest::Client as ReqwestClient;
+/// use std::sync::Arc;
+///
+/// #[tokio::main]
+/// async fn main() -> discord_bots_org::Result<()> {
+///     let client = ApiClient::new(Arc::new(ReqwestClient::new()));
+///     let bot = client.get_bot(270_198_738_570_444_801).await?;
+///
+///     println!("The bot's name is: {}", bot.username);
+///
+///     Ok(())
+/// }
 /// ```
-#[derive(Clone, Debug)]
-pub struct Client {
-    inner: Arc<ReqwestClient>,
-}
+///
+/// [`bridge::AsyncClient`]: ../../struct.AsyncClient.html
+/// [`ReqwestSyncClient`]: ../../../type.ReqwestSyncClient.html
+pub type Client = AsyncClient<Arc<ReqwestClient>>;
+
+impl AsyncBackend for ReqwestClient {
+    fn execute(
+        &self,
+        request: &Request,
+    ) -> impl Future<Output = Result<Response>> + Send {
+        let method = super::method(request.method);
+        let mut builder = self.request(method, &request.url);
+        let headers = request
+            .headers
+            .iter()
+            .map(|(name, value)| Ok((*name, HeaderValue::from_str(value)?)))
+            .collect::<Result<Vec<_>>>();
+
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
 
-impl Client {
-    /// Creates a new client to interact with the API.
-    ///
-    /// This accepts an existing reqwest Client so a single HTTP client may be
-    /// shared across your application.
-    ///
-    /// # Examples
-    ///
-    /// Create a new API client:
-    ///
-    /// ```rust
-    /// extern crate reqwest;
-    ///
-    /// use discord_bots_org::ReqwestAsyncClient as ApiClient;
-    /// use reqwest::r#async::Client as ReqwestClient;
-    /// use std::sync::Arc;
-    ///
-    /// let reqwest_client = Arc::new(ReqwestClient::new());
-    /// let api_client = ApiClient::new(Arc::clone(&reqwest_client));
-    pub fn new(reqwest_client: Arc<ReqwestClient>) -> Self {
-        Self {
-            inner: reqwest_client,
+        let send = async move {
+            for (name, value) in headers? {
+                builder = builder.header(name, value);
+            }
+
+            let resp = builder.send().await?;
+            let headers = super::headers(resp.headers());
+            let status = resp.status().as_u16();
+
+            Ok(Response {
+                body: resp.bytes().await?.to_vec(),
+                headers,
+                status,
+            })
+        };
+
+        async move {
+            if Handle::try_current().is_ok() {
+                return send.await;
+            }
+
+            let task = runtime().spawn(send);
+            let _abort = AbortOnDrop(task.abort_handle());
+
+            match task.await {
+                Ok(result) => result,
+                Err(why) => panic::resume_unwind(why.into_panic()),
+            }
         }
     }
+}
 
-    /// Retrieves information about a bot.
-    pub async fn get_bot(&self, user_id: u64) -> Result<Bot> {
-        await!(self.get(Url::parse(&endpoints::bot(user_id))?))
-    }
+/// Aborts a task on the background runtime if its future is dropped.
+struct AbortOnDrop(AbortHandle);
 
-    /// Retrieves a list of bots via a search.
-    pub async fn get_bots<'a>(
-        &'a self,
-        params: Vec<(&'a str, String)>,
-    ) -> Result<SearchResponse<Bot>> {
-        await!(self.get(Url::parse_with_params(&endpoints::bots(), params)?))
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
     }
+}
 
-    /// Retrieves information about a bot's specific stats.
-    pub async fn get_bot_stats(&self, user_id: u64) -> Result<BotStats> {
-        await!(self.get(Url::parse(&endpoints::bot_stats(user_id))?))
-    }
+/// The background runtime for requests polled outside of a Tokio runtime.
+fn runtime() -> &'static Handle {
+    static RUNTIME: OnceLock<Handle> = OnceLock::new();
 
-    /// Retrieve whether a user has upvoted a bot in the last 24 hours.
-    ///
-    /// You can use this if your bot has over 1000 votes.
-    pub async fn get_bot_vote_check<'a>(
-        &'a self,
-        auth: impl AsRef<str> + 'a,
-        bot_id: u64,
-        user_id: u64,
-    ) -> Result<bool> {
-        let path = endpoints::bot_vote_check(bot_id, user_id);
-        let params = &[("userId", user_id.to_string())];
-        let url = Url::parse_with_params(&path, params)?;
-        let (k, v) = (AUTHORIZATION, HeaderValue::from_str(auth.as_ref())?);
-
-        let mut resp = await!(self.inner.get(url).header(k, v).send().compat())?;
-        let body = await!(resp.json::<ResponseUserVoted>().compat())?;
-
-        Ok(body.voted == 1)
-    }
+    RUNTIME.get_or_init(|| {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("the reqwest runtime could not be built");
+        let handle = runtime.handle().clone();
 
-    /// Retrieves information to see who has upvoted a bot.
-    ///
-    /// **Note**: If your bot has over 1000 votes per month, then this can not
-    /// be used. Webhooks must instead be used.
-    pub async fn get_bot_votes<'a>(
-        &'a self,
-        auth: impl AsRef<str> + 'a,
-        bot_id: u64,
-    ) -> Result<BotVotes> {
-        let url = Url::parse(&endpoints::bot_votes(bot_id))?;
-        let (k, v) = (AUTHORIZATION, HeaderValue::from_str(auth.as_ref())?);
-        let mut resp = await!(self.inner.get(url).header(k, v).send().compat())?;
-
-        await!(resp.json().compat()).map_err(From::from)
-    }
+        thread::Builder::new()
+            .name("discord-bots-org-reqwest".to_owned())
+            .spawn(move || runtime.block_on(future::pending::<()>()))
+            .expect("the reqwest runtime thread could not be spawned");
 
-    /// Retrieves information about a user.
-    pub async fn get_user(&self, user_id: u64) -> Result<User> {
-        await!(self.get(Url::parse(&endpoints::user(user_id))?))
-    }
+        handle
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{executor::block_on, mock::MockServer, route::Route, Result};
+    use reqwest::Client as ReqwestClient;
+    use std::sync::Arc;
+    use super::Client;
+
+    #[test]
+    fn test_any_executor() -> Result<()> {
+        let route = Route::GetBotStats { id: 1 };
+        let body = r#"{"serverCount":5,"shards":[]}"#;
+        let server = MockServer::start(&[(route, 200, body)]);
+        let reqwest = Arc::new(ReqwestClient::new());
+        let client = Client::with_base(reqwest, server.url());
 
-    /// Posts a bot's shard stats.
-    pub async fn post_stats<'a>(
-        &'a self,
-        auth: impl AsRef<str> + 'a,
-        bot_id: u64,
-        stats: &'a ShardStats,
-    ) -> Result<()> {
-        let url = Url::parse(&endpoints::bot_stats(bot_id))?;
-        let (k, v) = (AUTHORIZATION, HeaderValue::from_str(auth.as_ref())?);
-        await!(self
-            .inner
-            .post(url)
-            .header(k, v)
-            .json(stats)
-            .send()
-            .compat())?;
+        let stats = block_on(client.get_bot_stats(1))?;
+        assert_eq!(stats.server_count, Some(5));
 
         Ok(())
     }
 
-    async fn get<'a, T: DeserializeOwned>(&'a self, url: Url) -> Result<T> {
-        let mut resp = await!(self.inner.get(url).send().compat())?;
+    #[tokio::test]
+    async fn test_tokio() -> Result<()> {
+        let body = r#"{"is_weekend":true}"#;
+        let server = MockServer::start(&[(Route::GetWeekend, 200, body)]);
+        let reqwest = Arc::new(ReqwestClient::new());
+        let client = Client::with_base(reqwest, server.url());
 
-        await!(resp.json::<T>().compat()).map_err(From::from)
+        assert!(client.get_weekend("auth").await?);
+        assert_eq!(server.requests()[0].authorization.as_deref(), Some("auth"));
+
+        Ok(())
     }
 }
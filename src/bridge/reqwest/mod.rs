@@ -5,3 +5,22 @@ pub mod r#async;
 
 #[cfg(feature = "reqwest-sync-support")]
 pub mod sync;
+
+use reqwest::header::HeaderMap;
+use super::operation::Method;
+
+fn headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+        })
+        .collect()
+}
+
+fn method(method: Method) -> reqwest::Method {
+    match method {
+        Method::Get => reqwest::Method::GET,
+        Method::Post => reqwest::Method::POST,
+    }
+}
@@ -0,0 +1,139 @@
+//! Bridge to provide a client implementation for the `ureq` crate.
+//!
+//! # Examples
+//!
+//! Refer to the documentation for [`Client`].
+//!
+//! [`Client`]: type.Client.html
+
+use crate::{
+    bridge::{
+        operation::{Request, Response},
+        Backend,
+        Client as BridgeClient,
+    },
+    Result,
+};
+use ureq::{http::Request as HttpRequest, Agent, Error as UreqError};
+
+/// Struct which defines the methods necessary to interact with the service.
+///
+/// This is a [`bridge::Client`] on a ureq Agent, without an async runtime.
+/// The agent is cheap to clone, so a single connection pool may be shared
+/// across your application.
+///
+/// # Examples
+///
+/// Request a bot by ID:
+///
+/// ```rust,no_run
+/// use discord_bots_org::bridge::ureq::Client as ApiClient;
+/// use ureq::Agent;
+///
+/// let client = ApiClient::new(Agent::new_with_defaults());
+/// let bot = client.get_bot(270_198_738_570_444_801)?;
+///
+/// println!("The bot's name is: {}", bot.username);
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// [`bridge::Client`]: ../struct.Client.html
+pub type Client = BridgeClient<Agent>;
+
+impl Backend for Agent {
+    fn execute(&self, request: &Request) -> Result<Response> {
+        let mut builder = HttpRequest::builder()
+            .method(request.method.as_str())
+            .uri(request.url.as_str());
+
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value.as_str());
+        }
+
+        let body = request.body.clone().unwrap_or_default();
+        let request = builder.body(body).map_err(UreqError::from)?;
+        let request = self
+            .configure_request(request)
+            .http_status_as_error(false)
+            .build();
+
+        let mut resp = self.run(request)?;
+        let headers = resp
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.to_string(), value.to_str().ok()?.to_owned()))
+            })
+            .collect();
+        let status = resp.status().as_u16();
+
+        Ok(Response {
+            body: resp.body_mut().read_to_vec()?,
+            headers,
+            status,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{mock::MockServer, model::ShardStats, route::Route, Error};
+    use super::Client;
+    use ureq::Agent;
+
+    const STATS: &str = r#"{"serverCount":5,"shards":[]}"#;
+
+    fn client(server: &MockServer) -> Client {
+        Client::with_base(Agent::new_with_defaults(), server.url())
+    }
+
+    #[test]
+    fn test_get() {
+        let server = MockServer::start(&[
+            (Route::GetBotStats { id: 1 }, 200, STATS),
+            (
+                Route::GetBotVoteCheck { bot_id: 1, user_id: 2 },
+                200,
+                r#"{"voted":1}"#,
+            ),
+            (Route::GetWeekend, 200, r#"{"is_weekend":false}"#),
+        ]);
+        let client = client(&server);
+
+        let stats = client.get_bot_stats(1).unwrap();
+        assert_eq!(stats.server_count, Some(5));
+        assert!(client.get_bot_vote_check("auth", 1, 2).unwrap());
+        assert!(!client.get_weekend("auth").unwrap());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].authorization, None);
+        assert_eq!(requests[1].authorization.as_deref(), Some("auth"));
+        assert_eq!(requests[1].path, "/bots/1/check?userId=2");
+        assert_eq!(requests[2].path, "/weekend");
+    }
+
+    #[test]
+    fn test_post_stats() {
+        let route = Route::PostBotStats { id: 1 };
+        let server = MockServer::start(&[(route, 200, "{}")]);
+        let stats = ShardStats::Shards(vec![1, 2]);
+
+        client(&server).post_stats("auth", 1, &stats).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].authorization.as_deref(), Some("auth"));
+        let body = serde_json::from_str::<Vec<u64>>(&requests[0].body);
+        assert_eq!(body.unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_status_error() {
+        let server = MockServer::start(&[]);
+
+        match client(&server).get_user(1) {
+            Err(Error::Status(resp)) => assert_eq!(resp.status, 404),
+            other => panic!("expected a 404, got {:?}", other),
+        }
+    }
+}
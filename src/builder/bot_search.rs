@@ -54,7 +54,7 @@ impl BotSearch {
     /// assert_eq!(params.len(), 2);
     /// ```
     pub fn build(self) -> Vec<(&'static str, String)> {
-        self.0.into_iter().map(|(k, v)| (k, v)).collect()
+        self.0.into_iter().collect()
     }
 
     /// The amount of bots to return, used for pagination.
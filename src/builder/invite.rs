@@ -0,0 +1,462 @@
+//! Types for generating and inspecting OAuth2 bot invite URLs.
+
+use bitflags::bitflags;
+use crate::{endpoints, model::Bot, Error, Result};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use url::Url;
+
+bitflags! {
+    /// Discord permissions that a bot may request when being invited.
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub struct Permissions: u64 {
+        /// Allows the creation of instant invites.
+        const CREATE_INSTANT_INVITE = 1;
+        /// Allows kicking members.
+        const KICK_MEMBERS = 1 << 1;
+        /// Allows banning members.
+        const BAN_MEMBERS = 1 << 2;
+        /// Grants all permissions and bypasses channel overwrites.
+        const ADMINISTRATOR = 1 << 3;
+        /// Allows management and editing of channels.
+        const MANAGE_CHANNELS = 1 << 4;
+        /// Allows management and editing of the guild.
+        const MANAGE_GUILD = 1 << 5;
+        /// Allows adding reactions to messages.
+        const ADD_REACTIONS = 1 << 6;
+        /// Allows viewing the audit log.
+        const VIEW_AUDIT_LOG = 1 << 7;
+        /// Allows using priority speaker in a voice channel.
+        const PRIORITY_SPEAKER = 1 << 8;
+        /// Allows streaming in a voice channel.
+        const STREAM = 1 << 9;
+        /// Allows viewing a channel.
+        const VIEW_CHANNEL = 1 << 10;
+        /// Allows sending messages in a channel.
+        const SEND_MESSAGES = 1 << 11;
+        /// Allows sending text-to-speech messages.
+        const SEND_TTS_MESSAGES = 1 << 12;
+        /// Allows deleting and pinning messages of other users.
+        const MANAGE_MESSAGES = 1 << 13;
+        /// Allows links sent by the user to be embedded.
+        const EMBED_LINKS = 1 << 14;
+        /// Allows uploading files.
+        const ATTACH_FILES = 1 << 15;
+        /// Allows reading the message history of a channel.
+        const READ_MESSAGE_HISTORY = 1 << 16;
+        /// Allows mentioning `@everyone` and `@here`.
+        const MENTION_EVERYONE = 1 << 17;
+        /// Allows using emojis from other guilds.
+        const USE_EXTERNAL_EMOJIS = 1 << 18;
+        /// Allows viewing guild insights.
+        const VIEW_GUILD_INSIGHTS = 1 << 19;
+        /// Allows connecting to a voice channel.
+        const CONNECT = 1 << 20;
+        /// Allows speaking in a voice channel.
+        const SPEAK = 1 << 21;
+        /// Allows muting members in a voice channel.
+        const MUTE_MEMBERS = 1 << 22;
+        /// Allows deafening members in a voice channel.
+        const DEAFEN_MEMBERS = 1 << 23;
+        /// Allows moving members between voice channels.
+        const MOVE_MEMBERS = 1 << 24;
+        /// Allows using voice activity detection.
+        const USE_VAD = 1 << 25;
+        /// Allows changing one's own nickname.
+        const CHANGE_NICKNAME = 1 << 26;
+        /// Allows changing the nicknames of other members.
+        const MANAGE_NICKNAMES = 1 << 27;
+        /// Allows management and editing of roles.
+        const MANAGE_ROLES = 1 << 28;
+        /// Allows management and editing of webhooks.
+        const MANAGE_WEBHOOKS = 1 << 29;
+        /// Allows management of emojis, stickers and soundboard sounds.
+        const MANAGE_GUILD_EXPRESSIONS = 1 << 30;
+        /// Allows using application commands.
+        const USE_APPLICATION_COMMANDS = 1 << 31;
+        /// Allows requesting to speak in stage channels.
+        const REQUEST_TO_SPEAK = 1 << 32;
+        /// Allows management of scheduled events.
+        const MANAGE_EVENTS = 1 << 33;
+        /// Allows management of threads.
+        const MANAGE_THREADS = 1 << 34;
+        /// Allows creating public threads.
+        const CREATE_PUBLIC_THREADS = 1 << 35;
+        /// Allows creating private threads.
+        const CREATE_PRIVATE_THREADS = 1 << 36;
+        /// Allows using stickers from other guilds.
+        const USE_EXTERNAL_STICKERS = 1 << 37;
+        /// Allows sending messages in threads.
+        const SEND_MESSAGES_IN_THREADS = 1 << 38;
+        /// Allows using activities in voice channels.
+        const USE_EMBEDDED_ACTIVITIES = 1 << 39;
+        /// Allows timing out members.
+        const MODERATE_MEMBERS = 1 << 40;
+        /// Allows viewing role subscription insights.
+        const VIEW_CREATOR_MONETIZATION_ANALYTICS = 1 << 41;
+        /// Allows using the soundboard in a voice channel.
+        const USE_SOUNDBOARD = 1 << 42;
+        /// Allows creating emojis, stickers and soundboard sounds.
+        const CREATE_GUILD_EXPRESSIONS = 1 << 43;
+        /// Allows creating scheduled events.
+        const CREATE_EVENTS = 1 << 44;
+        /// Allows using soundboard sounds from other guilds.
+        const USE_EXTERNAL_SOUNDS = 1 << 45;
+        /// Allows sending voice messages.
+        const SEND_VOICE_MESSAGES = 1 << 46;
+        /// Allows sending polls.
+        const SEND_POLLS = 1 << 49;
+        /// Allows user-installed apps to send public responses.
+        const USE_EXTERNAL_APPS = 1 << 50;
+    }
+}
+
+/// An OAuth2 scope requested by an invite.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Scope {
+    /// Allows the creation of application commands in a guild.
+    ApplicationsCommands,
+    /// Adds the bot user to a guild.
+    Bot,
+    /// Allows viewing the user's connected accounts.
+    Connections,
+    /// Allows viewing the user's email address.
+    Email,
+    /// Allows viewing the guilds the user is in.
+    Guilds,
+    /// Allows joining guilds on the user's behalf.
+    GuildsJoin,
+    /// Allows viewing the user's account information.
+    Identify,
+    /// Generates a webhook in a channel of the user's choosing.
+    WebhookIncoming,
+    /// A scope unknown to the library.
+    Other(String),
+}
+
+impl Scope {
+    fn name(&self) -> &str {
+        match self {
+            Scope::ApplicationsCommands => "applications.commands",
+            Scope::Bot => "bot",
+            Scope::Connections => "connections",
+            Scope::Email => "email",
+            Scope::Guilds => "guilds",
+            Scope::GuildsJoin => "guilds.join",
+            Scope::Identify => "identify",
+            Scope::WebhookIncoming => "webhook.incoming",
+            Scope::Other(name) => name,
+        }
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str(self.name())
+    }
+}
+
+impl From<&str> for Scope {
+    fn from(name: &str) -> Self {
+        match name {
+            "applications.commands" => Scope::ApplicationsCommands,
+            "bot" => Scope::Bot,
+            "connections" => Scope::Connections,
+            "email" => Scope::Email,
+            "guilds" => Scope::Guilds,
+            "guilds.join" => Scope::GuildsJoin,
+            "identify" => Scope::Identify,
+            "webhook.incoming" => Scope::WebhookIncoming,
+            other => Scope::Other(other.to_owned()),
+        }
+    }
+}
+
+/// Builder to create an OAuth2 invite URL for a bot, or to inspect an existing
+/// one.
+///
+/// # Examples
+///
+/// Create an invite URL requesting permission to send messages and embed
+/// links:
+///
+/// ```rust
+/// use discord_bots_org::builder::invite::{Invite, Permissions};
+///
+/// let mut invite = Invite::new(1);
+/// invite.permissions(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS);
+///
+/// let url = invite.build()?;
+///
+/// assert!(url.contains("permissions=18432"));
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// Audit the permissions requested by an existing invite URL:
+///
+/// ```rust
+/// use discord_bots_org::builder::invite::{Invite, Permissions};
+///
+/// let url = "https://discord.com/oauth2/authorize?client_id=1&permissions=8";
+/// let invite = Invite::parse(url)?;
+/// let permissions = invite.requested_permissions();
+///
+/// assert!(permissions.contains(Permissions::ADMINISTRATOR));
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Invite {
+    client_id: u64,
+    disable_guild_select: bool,
+    guild_id: Option<u64>,
+    permissions: Permissions,
+    scopes: Vec<Scope>,
+}
+
+impl Invite {
+    /// Creates a new builder for inviting a bot by ID.
+    ///
+    /// The [`Scope::Bot`] scope is requested by default.
+    ///
+    /// [`Scope::Bot`]: enum.Scope.html#variant.Bot
+    pub fn new(client_id: u64) -> Self {
+        Self {
+            client_id,
+            disable_guild_select: false,
+            guild_id: None,
+            permissions: Permissions::empty(),
+            scopes: vec![Scope::Bot],
+        }
+    }
+
+    /// Creates a new builder for inviting the given bot.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidId`] if the bot's ID is not numeric.
+    ///
+    /// [`Error::InvalidId`]: ../../enum.Error.html#variant.InvalidId
+    pub fn from_bot(bot: &Bot) -> Result<Self> {
+        Ok(Self::new(bot.id.parse().map_err(Error::InvalidId)?))
+    }
+
+    /// Parses an existing invite URL, such as [`Bot::invite`], into its parts.
+    ///
+    /// Permission bits unknown to the library are retained.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidUrl`] if the value is not a URL, or
+    /// [`Error::InvalidInvite`] if it is not a Discord OAuth2 authorization
+    /// URL with a valid client ID.
+    ///
+    /// [`Bot::invite`]: ../../model/struct.Bot.html#structfield.invite
+    /// [`Error::InvalidInvite`]: ../../enum.Error.html#variant.InvalidInvite
+    /// [`Error::InvalidUrl`]: ../../enum.Error.html#variant.InvalidUrl
+    pub fn parse(url: impl AsRef<str>) -> Result<Self> {
+        Self::_parse(url.as_ref())
+    }
+
+    fn _parse(url: &str) -> Result<Self> {
+        let url = Url::parse(url)?;
+
+        let host = url.host_str().unwrap_or_default();
+        let is_discord = ["discord.com", "discordapp.com"].iter().any(|domain| {
+            host == *domain || host.ends_with(&format!(".{}", domain))
+        });
+        let path = url.path().trim_end_matches('/');
+
+        if !is_discord || !path.ends_with("/oauth2/authorize") {
+            return Err(Error::InvalidInvite);
+        }
+
+        let mut client_id = None;
+        let mut invite = Self::new(0);
+        invite.scopes.clear();
+
+        for (key, value) in url.query_pairs() {
+            match &*key {
+                "client_id" => client_id = Some(parse_id(&value)?),
+                "disable_guild_select" => {
+                    invite.disable_guild_select = value == "true";
+                },
+                "guild_id" => invite.guild_id = Some(parse_id(&value)?),
+                "permissions" => {
+                    let bits = parse_id(&value)?;
+                    invite.permissions = Permissions::from_bits_retain(bits);
+                },
+                "scope" => {
+                    invite.scopes = value
+                        .split_whitespace()
+                        .map(From::from)
+                        .collect();
+                },
+                _ => {},
+            }
+        }
+
+        invite.client_id = client_id.ok_or(Error::InvalidInvite)?;
+
+        Ok(invite)
+    }
+
+    /// Builds into a valid URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidUrl`] if one of the query parameters is invalid.
+    ///
+    /// [`Error::InvalidUrl`]: ../../enum.Error.html#variant.InvalidUrl
+    pub fn build(self) -> Result<String> {
+        let scopes = self
+            .scopes
+            .iter()
+            .map(Scope::name)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut params = vec![
+            ("client_id", self.client_id.to_string()),
+            ("scope", scopes),
+            ("permissions", self.permissions.bits().to_string()),
+        ];
+
+        if let Some(guild_id) = self.guild_id {
+            params.push(("guild_id", guild_id.to_string()));
+        }
+
+        if self.disable_guild_select {
+            params.push(("disable_guild_select", "true".to_owned()));
+        }
+
+        let uri = endpoints::oauth2_authorize();
+        let url = Url::parse_with_params(&uri, params)?;
+
+        Ok(url.into())
+    }
+
+    /// Sets whether the user is prevented from choosing a guild other than
+    /// the [preselected one].
+    ///
+    /// [preselected one]: #method.guild
+    pub fn disable_guild_select(&mut self, value: bool) -> &mut Self {
+        self.disable_guild_select = value;
+
+        self
+    }
+
+    /// Preselects the guild to add the bot to.
+    pub fn guild(&mut self, guild_id: u64) -> &mut Self {
+        self.guild_id = Some(guild_id);
+
+        self
+    }
+
+    /// Sets the permissions to request.
+    pub fn permissions(&mut self, permissions: Permissions) -> &mut Self {
+        self.permissions = permissions;
+
+        self
+    }
+
+    /// Adds a scope to request, if not already requested.
+    pub fn scope(&mut self, scope: Scope) -> &mut Self {
+        if !self.scopes.contains(&scope) {
+            self.scopes.push(scope);
+        }
+
+        self
+    }
+
+    /// The ID of the bot being invited.
+    pub fn client_id(&self) -> u64 {
+        self.client_id
+    }
+
+    /// Whether guild selection is disabled.
+    pub fn guild_select_disabled(&self) -> bool {
+        self.disable_guild_select
+    }
+
+    /// The ID of the preselected guild, if any.
+    pub fn guild_id(&self) -> Option<u64> {
+        self.guild_id
+    }
+
+    /// The requested permissions.
+    pub fn requested_permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    /// The requested scopes.
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+}
+
+fn parse_id(value: &str) -> Result<u64> {
+    value.parse().map_err(|_| Error::InvalidInvite)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use super::{Invite, Permissions, Scope};
+
+    #[test]
+    fn test_build() -> Result<()> {
+        let mut invite = Invite::new(1);
+        invite
+            .permissions(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS)
+            .scope(Scope::ApplicationsCommands)
+            .guild(2)
+            .disable_guild_select(true);
+
+        let url = invite.build()?;
+        assert!(url.starts_with("https://discord.com/oauth2/authorize?"));
+        assert!(url.contains("client_id=1"));
+        assert!(url.contains("scope=bot+applications.commands"));
+        assert!(url.contains("permissions=18432"));
+        assert!(url.contains("guild_id=2"));
+        assert!(url.contains("disable_guild_select=true"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let url = "https://discordapp.com/api/oauth2/authorize?client_id=1\
+            &permissions=2251799813685256&scope=bot%20applications.commands\
+            &guild_id=2&disable_guild_select=true";
+        let invite = Invite::parse(url)?;
+
+        assert_eq!(invite.client_id(), 1);
+        assert_eq!(invite.guild_id(), Some(2));
+        assert!(invite.guild_select_disabled());
+        assert_eq!(invite.scopes(), &[Scope::Bot, Scope::ApplicationsCommands]);
+        let permissions = invite.requested_permissions();
+        assert!(permissions.contains(Permissions::ADMINISTRATOR));
+        assert_eq!(permissions.bits(), 2_251_799_813_685_256);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_roundtrip() -> Result<()> {
+        let mut invite = Invite::new(1);
+        invite.permissions(Permissions::ADMINISTRATOR).guild(3);
+
+        assert_eq!(Invite::parse(invite.clone().build()?)?, invite);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let base = "https://discord.com/oauth2/authorize";
+
+        assert!(Invite::parse("https://example.com/oauth2/authorize").is_err());
+        assert!(Invite::parse(format!("{}?scope=bot", base)).is_err());
+        assert!(Invite::parse(format!("{}?client_id=a", base)).is_err());
+        assert!(Invite::parse("not a url").is_err());
+    }
+}
@@ -1,8 +1,10 @@
 //! A set of builders useful for building optional portions of request
 //! parameters and other miscellaneous tasks.
 
+pub mod invite;
 pub mod widget;
 
 mod bot_search;
+mod vote_page;
 
-pub use self::bot_search::BotSearch;
+pub use self::{bot_search::BotSearch, vote_page::VotePage};
@@ -0,0 +1,105 @@
+use crate::{endpoints, Result};
+use url::Url;
+
+/// A builder for creating a URL to a bot's vote page with query params.
+///
+/// The query params are included in the [`Webhook`] sent when a user votes
+/// through the URL, such as to track which campaign a vote came from.
+///
+/// # Examples
+///
+/// ```rust
+/// use discord_bots_org::builder::VotePage;
+///
+/// let mut page = VotePage::new(270198738570444801);
+/// page.param("campaign", "spring sale");
+///
+/// assert!(page.build()?.ends_with("/vote?campaign=spring+sale"));
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// [`Webhook`]: ../model/struct.Webhook.html
+#[derive(Clone, Debug)]
+pub struct VotePage {
+    bot_id: u64,
+    params: Vec<(String, String)>,
+}
+
+impl VotePage {
+    /// Creates a new builder for the vote page of the bot with the given ID.
+    pub fn new(bot_id: u64) -> Self {
+        Self {
+            bot_id,
+            params: Vec::new(),
+        }
+    }
+
+    /// Builds into a valid URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidUrl`] if the URL could not be built.
+    ///
+    /// [`Error::InvalidUrl`]: ../enum.Error.html#variant.InvalidUrl
+    pub fn build(self) -> Result<String> {
+        let uri = endpoints::vote_page(self.bot_id);
+
+        let url = if self.params.is_empty() {
+            Url::parse(&uri)?
+        } else {
+            Url::parse_with_params(&uri, self.params)?
+        };
+
+        Ok(url.into())
+    }
+
+    /// Adds a query param.
+    ///
+    /// Params are kept in the order they're added, and a key can be added
+    /// multiple times.
+    pub fn param(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> &mut Self {
+        self.params.push((key.into(), value.into()));
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        model::{Webhook, WebhookType},
+        Result,
+    };
+    use super::VotePage;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut page = VotePage::new(1);
+        assert_eq!(
+            page.clone().build()?,
+            "https://discordbots.org/bot/1/vote",
+        );
+
+        page.param("ref", "a&b").param("ref", "c d");
+        let url = page.build()?;
+        assert_eq!(url, "https://discordbots.org/bot/1/vote?ref=a%26b&ref=c+d");
+
+        let webhook = Webhook {
+            bot: "1".to_owned(),
+            is_weekend: false,
+            kind: WebhookType::Upvote,
+            query: url[url.find('?').unwrap()..].to_owned(),
+            user_id: "2".to_owned(),
+        };
+        assert_eq!(webhook.query_params(), vec![
+            ("ref".to_owned(), "a&b".to_owned()),
+            ("ref".to_owned(), "c d".to_owned()),
+        ]);
+
+        Ok(())
+    }
+}
@@ -15,15 +15,9 @@ impl Widget {
             endpoints::widget(self.0)
         };
 
-        let params = self
-            .1
-            .into_iter()
-            .map(|(k, v)| (k, v))
-            .collect::<Vec<_>>();
+        let url = Url::parse_with_params(&uri, self.1)?;
 
-        let url = Url::parse_with_params(&uri, params)?;
-
-        Ok(url.into_string())
+        Ok(url.into())
     }
 
     fn insert(&mut self, k: &'static str, v: impl Into<String>) -> &mut Self {
@@ -0,0 +1,465 @@
+//! Utilities for mirroring the full list of bots into local storage.
+//!
+//! A [`Crawler`] pages through the bot list with [`Client::get_bots`],
+//! saving each page to a [`Store`] along with a [`Checkpoint`] of the offset
+//! reached. An interrupted crawl resumes from its checkpoint when run again,
+//! and each completed crawl is recorded as a [`Snapshot`], so successive
+//! crawls can be compared.
+//!
+//! As the list is paged by offset, bots which are added or removed during a
+//! crawl shift the pages after them. A bot shifted onto a later page is only
+//! saved once per snapshot, but a bot shifted onto an earlier page may be
+//! missed until the next crawl.
+//!
+//! # Examples
+//!
+//! Mirror the bot list into a directory, resuming any interrupted crawl:
+//!
+//! ```rust,no_run
+//! use discord_bots_org::{
+//!     bridge::{
+//!         ratelimit::{RateLimited, RateLimiter},
+//!         Client,
+//!     },
+//!     crawl::{Crawler, FileStore},
+//! };
+//! use reqwest::blocking::Client as ReqwestClient;
+//!
+//! let backend = RateLimited::new(ReqwestClient::new(), RateLimiter::new());
+//! let client = Client::new(backend);
+//! let mut crawler = Crawler::new(&client, FileStore::open("mirror")?);
+//!
+//! let snapshot = crawler.run()?;
+//! println!("Saved {} bots", snapshot.count);
+//! # Ok::<(), discord_bots_org::Error>(())
+//! ```
+//!
+//! [`Checkpoint`]: struct.Checkpoint.html
+//! [`Client::get_bots`]: ../bridge/struct.Client.html#method.get_bots
+//! [`Crawler`]: struct.Crawler.html
+//! [`Snapshot`]: struct.Snapshot.html
+//! [`Store`]: trait.Store.html
+
+mod store;
+
+pub use self::store::{FileStore, MemoryStore, Store};
+
+use chrono::{DateTime, Utc};
+use crate::{
+    bridge::{operation::Response, Backend, Client},
+    model::Bot,
+    vote::{Clock, SystemClock},
+    Error,
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, thread, time::Duration};
+
+/// The most times a page is retried after being rate limited.
+const MAX_RETRIES: u32 = 5;
+
+/// The progress of a crawl which hasn't finished.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    /// The number of bots saved so far.
+    pub count: u64,
+    /// The offset of the next page to retrieve.
+    pub offset: u64,
+    /// When the crawl started.
+    pub started_at: DateTime<Utc>,
+    /// The total number of bots, as of the last page retrieved.
+    pub total: u64,
+}
+
+/// A bot saved by a crawl.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrawledBot {
+    /// The bot.
+    #[serde(flatten)]
+    pub bot: Bot,
+    /// When the bot was retrieved.
+    pub crawled_at: DateTime<Utc>,
+}
+
+/// A completed crawl.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// The number of bots saved.
+    pub count: u64,
+    /// When the crawl finished.
+    pub finished_at: DateTime<Utc>,
+    /// When the crawl started.
+    pub started_at: DateTime<Utc>,
+}
+
+/// Crawls the full list of bots into a [`Store`].
+///
+/// Requests are rate limited if the client's backend is, such as a
+/// [`RateLimited`] backend. Pages which are rate limited anyway are retried
+/// after their `Retry-After` header, up to 5 times. If a request otherwise
+/// fails, the error is returned and the crawl can be resumed by running it
+/// again.
+///
+/// Bots already saved by the crawl, such as because the list shifted
+/// between pages, are skipped.
+///
+/// [`RateLimited`]: ../bridge/ratelimit/struct.RateLimited.html
+/// [`Store`]: trait.Store.html
+#[derive(Debug)]
+pub struct Crawler<'a, B, S, C = SystemClock> {
+    client: &'a Client<B>,
+    clock: C,
+    page_size: u16,
+    store: S,
+}
+
+impl<'a, B: Backend, S: Store> Crawler<'a, B, S> {
+    /// Creates a new crawler saving bots to a store.
+    pub fn new(client: &'a Client<B>, store: S) -> Self {
+        Self::with_clock(client, store, SystemClock)
+    }
+}
+
+impl<'a, B: Backend, S: Store, C: Clock> Crawler<'a, B, S, C> {
+    /// Creates a new crawler which uses the given clock for timestamps.
+    pub fn with_clock(client: &'a Client<B>, store: S, clock: C) -> Self {
+        Self {
+            client,
+            clock,
+            page_size: 500,
+            store,
+        }
+    }
+
+    /// Sets the number of bots to retrieve per request.
+    ///
+    /// Defaults to 500, the most allowed.
+    pub fn page_size(&mut self, page_size: u16) -> &mut Self {
+        self.page_size = page_size.clamp(1, 500);
+
+        self
+    }
+
+    /// The store which bots are saved to.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Crawls every bot, resuming from the store's checkpoint if there is
+    /// one, and returns the completed snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a page could not be retrieved or saved. Pages
+    /// saved before the error are kept.
+    pub fn run(&mut self) -> Result<Snapshot> {
+        let (mut checkpoint, mut seen) = match self.store.checkpoint() {
+            Some(checkpoint) => (checkpoint, self.store.crawled_ids()?),
+            None => {
+                let checkpoint = Checkpoint {
+                    count: 0,
+                    offset: 0,
+                    started_at: self.clock.now(),
+                    total: 0,
+                };
+
+                (checkpoint, HashSet::new())
+            },
+        };
+        let mut retries = 0;
+
+        loop {
+            let (limit, offset) = (self.page_size, checkpoint.offset);
+            let result = self.client.get_bots(|mut search| {
+                search.limit(limit).offset(offset);
+
+                search
+            });
+            let page = match result {
+                Ok(page) => page,
+                Err(Error::Status(resp))
+                    if resp.status == 429 && retries < MAX_RETRIES =>
+                {
+                    retries += 1;
+                    thread::sleep(retry_after(&resp));
+
+                    continue;
+                },
+                Err(why) => return Err(why),
+            };
+            let crawled_at = self.clock.now();
+            let received = page.results.len() as u64;
+            let bots = page
+                .results
+                .into_iter()
+                .filter(|bot| seen.insert(bot.id.clone()))
+                .map(|bot| CrawledBot {
+                    bot,
+                    crawled_at,
+                })
+                .collect::<Vec<_>>();
+
+            retries = 0;
+            checkpoint.count += bots.len() as u64;
+            checkpoint.offset += received;
+            checkpoint.total = page.total;
+
+            if received > 0 {
+                self.store.save(&checkpoint, &bots)?;
+            }
+
+            if received == 0 || checkpoint.offset >= checkpoint.total {
+                let snapshot = Snapshot {
+                    count: checkpoint.count,
+                    finished_at: self.clock.now(),
+                    started_at: checkpoint.started_at,
+                };
+                self.store.finish(&snapshot)?;
+
+                return Ok(snapshot);
+            }
+        }
+    }
+}
+
+/// How long to wait before retrying a rate limited request, defaulting to a
+/// second if the response has no valid `Retry-After` header.
+fn retry_after(response: &Response) -> Duration {
+    response
+        .header("retry-after")
+        .and_then(|secs| secs.trim().parse::<f64>().ok())
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map_or(Duration::from_secs(1), Duration::from_secs_f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use crate::{
+        bridge::{
+            operation::{Request, Response},
+            Backend,
+            Client,
+        },
+        route::Route,
+        vote::ManualClock,
+        Error,
+        Result,
+    };
+    use serde_json::json;
+    use std::{
+        env,
+        fs::{self, OpenOptions},
+        io::Write,
+        process,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use super::{Crawler, FileStore, MemoryStore, Store};
+
+    /// A backend listing 5 bots, which fails after a number of pages.
+    ///
+    /// The first requests are rate limited, and if the list shifts then bot
+    /// 5 is added to the front of the list after the first page.
+    struct Pages {
+        limited: AtomicUsize,
+        remaining: AtomicUsize,
+        served: AtomicUsize,
+        shift: bool,
+    }
+
+    impl Backend for Pages {
+        fn execute(&self, request: &Request) -> Result<Response> {
+            let params = match &request.route {
+                Route::GetBots { params } => params,
+                _ => unreachable!(),
+            };
+            let param = |name| {
+                let (_, value) = params.iter().find(|p| p.0 == name).unwrap();
+
+                value.parse::<u64>().unwrap()
+            };
+            let (limit, offset) = (param("limit"), param("offset"));
+
+            if self.limited.load(Ordering::SeqCst) > 0 {
+                self.limited.fetch_sub(1, Ordering::SeqCst);
+
+                return Ok(Response {
+                    body: Vec::new(),
+                    headers: vec![("Retry-After".into(), "0".into())],
+                    status: 429,
+                });
+            }
+
+            if self.remaining.fetch_sub(1, Ordering::SeqCst) == 0 {
+                return Ok(Response {
+                    body: Vec::new(),
+                    headers: Vec::new(),
+                    status: 500,
+                });
+            }
+
+            let mut ids = (0..5).collect::<Vec<u64>>();
+
+            if self.shift && self.served.fetch_add(1, Ordering::SeqCst) > 0 {
+                ids.insert(0, 5);
+            }
+
+            let end = ids.len().min((offset + limit) as usize);
+            let results = ids[(offset as usize).min(end)..end]
+                .iter()
+                .map(|id| {
+                    json!({
+                        "certifiedBot": false,
+                        "date": "2019-01-01T00:00:00.000Z",
+                        "discriminator": "0001",
+                        "id": id.to_string(),
+                        "lib": "serenity",
+                        "owners": [],
+                        "points": 0,
+                        "prefix": "!",
+                        "shortdesc": "A bot.",
+                        "tags": [],
+                        "username": "Bot",
+                    })
+                })
+                .collect::<Vec<_>>();
+            let body = json!({
+                "count": results.len(),
+                "limit": limit,
+                "offset": offset,
+                "results": results,
+                "total": ids.len(),
+            });
+
+            Ok(Response {
+                body: body.to_string().into_bytes(),
+                headers: Vec::new(),
+                status: 200,
+            })
+        }
+    }
+
+    fn client(pages: usize) -> Client<Pages> {
+        Client::new(Pages {
+            limited: AtomicUsize::new(0),
+            remaining: AtomicUsize::new(pages),
+            served: AtomicUsize::new(0),
+            shift: false,
+        })
+    }
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let start = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let client = client(usize::MAX);
+        let mut crawler =
+            Crawler::with_clock(&client, MemoryStore::new(), clock.clone());
+        crawler.page_size(2);
+
+        let snapshot = crawler.run()?;
+        assert_eq!(snapshot.count, 5);
+        assert_eq!(snapshot.started_at, start);
+        assert!(crawler.store().checkpoint().is_none());
+        assert_eq!(crawler.store().bots().len(), 5);
+
+        // A second crawl is a separate snapshot.
+        clock.advance(Duration::days(1));
+        crawler.run()?;
+        let snapshots = crawler.store().snapshots();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].started_at, start + Duration::days(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_and_shift() -> Result<()> {
+        let client = Client::new(Pages {
+            limited: AtomicUsize::new(2),
+            remaining: AtomicUsize::new(usize::MAX),
+            served: AtomicUsize::new(0),
+            shift: true,
+        });
+        let mut crawler = Crawler::new(&client, MemoryStore::new());
+        crawler.page_size(2);
+
+        // Bot 1 is shifted onto the second page, but is only saved once.
+        let snapshot = crawler.run()?;
+        let bots = crawler.store().bots();
+        let ids = bots.iter().map(|b| b.bot.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["0", "1", "2", "3", "4"]);
+        assert_eq!(snapshot.count, 5);
+
+        // Pages which stay rate limited eventually fail the crawl.
+        let client = Client::new(Pages {
+            limited: AtomicUsize::new(6),
+            remaining: AtomicUsize::new(usize::MAX),
+            served: AtomicUsize::new(0),
+            shift: false,
+        });
+        let err = Crawler::new(&client, MemoryStore::new()).run().unwrap_err();
+        assert!(matches!(err, Error::Status(Response { status: 429, .. })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume() -> Result<()> {
+        let dir = env::temp_dir()
+            .join(format!("dbl-crawl-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        {
+            let client = client(2);
+            let mut crawler = Crawler::new(&client, FileStore::open(&dir)?);
+            crawler.page_size(2);
+
+            let err = crawler.run().unwrap_err();
+            assert!(matches!(err, Error::Status(Response { status: 500, .. })));
+            assert_eq!(crawler.store().checkpoint().unwrap().offset, 4);
+        }
+
+        // Bots written after the last checkpoint are discarded on opening.
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str());
+
+            if name.is_some_and(|name| name.starts_with("bots-")) {
+                let mut file = OpenOptions::new().append(true).open(path)?;
+                file.write_all(b"{\"partial\":true}\n")?;
+            }
+        }
+
+        let client = client(usize::MAX);
+        let mut crawler = Crawler::new(&client, FileStore::open(&dir)?);
+        crawler.page_size(2);
+        let snapshot = crawler.run()?;
+
+        let bots = crawler.store().bots(&snapshot)?;
+        let ids = bots.iter().map(|b| b.bot.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["0", "1", "2", "3", "4"]);
+
+        // A snapshot torn by a crash is discarded on opening.
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.join("snapshots.jsonl"))?;
+        file.write_all(b"{\"count\":")?;
+        drop(file);
+        assert_eq!(FileStore::open(&dir)?.snapshots().len(), 1);
+
+        // Crawls started within the same second are saved separately.
+        let mut crawler = Crawler::new(&client, FileStore::open(&dir)?);
+        let second = crawler.run()?;
+        assert_eq!(crawler.store().bots(&snapshot)?.len(), 5);
+        assert_eq!(crawler.store().bots(&second)?.len(), 5);
+        assert_eq!(crawler.store().snapshots(), [snapshot, second]);
+
+        let _ = fs::remove_dir_all(dir);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::{DateTime, Utc};
+use crate::{jsonl, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use super::{Checkpoint, CrawledBot, Snapshot};
+
+/// A store of crawled bots.
+pub trait Store {
+    /// The checkpoint of the crawl in progress, if any.
+    fn checkpoint(&self) -> Option<Checkpoint>;
+
+    /// The IDs of the bots saved by the crawl in progress, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the saved bots could not be read.
+    fn crawled_ids(&self) -> Result<HashSet<String>>;
+
+    /// Saves a page of bots, along with the checkpoint after it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the page could not be stored.
+    fn save(&mut self, checkpoint: &Checkpoint, bots: &[CrawledBot])
+        -> Result<()>;
+
+    /// Records a completed crawl, clearing its checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot could not be stored.
+    fn finish(&mut self, snapshot: &Snapshot) -> Result<()>;
+}
+
+/// A store which is only kept in memory.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore {
+    bots: Vec<CrawledBot>,
+    checkpoint: Option<Checkpoint>,
+    /// The number of bots saved by completed crawls.
+    finished: usize,
+    snapshots: Vec<Snapshot>,
+}
+
+impl MemoryStore {
+    /// Creates a new, empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bots saved by all crawls, in the order they were saved.
+    pub fn bots(&self) -> &[CrawledBot] {
+        &self.bots
+    }
+
+    /// The completed crawls, in the order they finished.
+    pub fn snapshots(&self) -> &[Snapshot] {
+        &self.snapshots
+    }
+}
+
+impl Store for MemoryStore {
+    fn checkpoint(&self) -> Option<Checkpoint> {
+        self.checkpoint.clone()
+    }
+
+    fn crawled_ids(&self) -> Result<HashSet<String>> {
+        let crawled = &self.bots[self.finished..];
+
+        Ok(crawled.iter().map(|crawled| crawled.bot.id.clone()).collect())
+    }
+
+    fn save(
+        &mut self,
+        checkpoint: &Checkpoint,
+        bots: &[CrawledBot],
+    ) -> Result<()> {
+        self.bots.extend_from_slice(bots);
+        self.checkpoint = Some(checkpoint.clone());
+
+        Ok(())
+    }
+
+    fn finish(&mut self, snapshot: &Snapshot) -> Result<()> {
+        self.checkpoint = None;
+        self.finished = self.bots.len();
+        self.snapshots.push(snapshot.clone());
+
+        Ok(())
+    }
+}
+
+/// The checkpoint file of a [`FileStore`].
+///
+/// [`FileStore`]: struct.FileStore.html
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Saved {
+    /// The length of the crawl's bots file when the checkpoint was saved.
+    bytes: u64,
+    #[serde(flatten)]
+    checkpoint: Checkpoint,
+}
+
+/// A store of JSON-lines files in a directory.
+///
+/// Each crawl's bots are saved to a file named by when it started, to the
+/// nanosecond, such as `bots-20190101T000000.000000000Z.jsonl`. Completed
+/// crawls are recorded in `snapshots.jsonl`, and the crawl in progress is
+/// checkpointed in `checkpoint.json`.
+///
+/// Each page is flushed to disk before its checkpoint is saved. Bots saved
+/// after the last checkpoint, such as if the process crashed, are discarded
+/// when the store is opened, so resuming a crawl never saves a bot twice.
+#[derive(Debug)]
+pub struct FileStore {
+    /// The bots file of the crawl in progress, once it has been opened.
+    bots: Option<File>,
+    dir: PathBuf,
+    saved: Option<(u64, Checkpoint)>,
+    snapshots: Vec<Snapshot>,
+    snapshots_file: File,
+}
+
+impl FileStore {
+    /// Opens the store in the given directory, creating it if it doesn't
+    /// exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the directory could not be read or created,
+    /// or [`Error::Json`] if its checkpoint or snapshots are invalid. A final
+    /// snapshot which was only partly written is removed instead.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [`Error::Json`]: ../enum.Error.html#variant.Json
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let path = dir.join("checkpoint.json");
+        let (bots, saved) = if path.exists() {
+            let saved = serde_json::from_reader::<_, Saved>(File::open(path)?)?;
+            let path = bots_path(dir, saved.checkpoint.started_at);
+            OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)?
+                .set_len(saved.bytes)?;
+            let (_, bots) = jsonl::open::<CrawledBot>(&path)?;
+
+            (Some(bots), Some((saved.bytes, saved.checkpoint)))
+        } else {
+            (None, None)
+        };
+        let (snapshots, snapshots_file) =
+            jsonl::open(&dir.join("snapshots.jsonl"))?;
+
+        Ok(Self {
+            bots,
+            dir: dir.to_owned(),
+            saved,
+            snapshots,
+            snapshots_file,
+        })
+    }
+
+    /// The bots saved by a completed crawl, in the order they were saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the crawl's file could not be read, or
+    /// [`Error::Json`] if it contains an invalid line.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [`Error::Json`]: ../enum.Error.html#variant.Json
+    pub fn bots(&self, snapshot: &Snapshot) -> Result<Vec<CrawledBot>> {
+        let path = bots_path(&self.dir, snapshot.started_at);
+        let (bots, _) = jsonl::open(&path)?;
+
+        Ok(bots)
+    }
+
+    /// The completed crawls, in the order they finished.
+    pub fn snapshots(&self) -> &[Snapshot] {
+        &self.snapshots
+    }
+}
+
+impl Store for FileStore {
+    fn checkpoint(&self) -> Option<Checkpoint> {
+        self.saved.as_ref().map(|(_, checkpoint)| checkpoint.clone())
+    }
+
+    fn crawled_ids(&self) -> Result<HashSet<String>> {
+        let started_at = match &self.saved {
+            Some((_, checkpoint)) => checkpoint.started_at,
+            None => return Ok(HashSet::new()),
+        };
+        let path = bots_path(&self.dir, started_at);
+        let (crawled, _) = jsonl::open::<CrawledBot>(&path)?;
+
+        Ok(crawled.into_iter().map(|crawled| crawled.bot.id).collect())
+    }
+
+    fn save(
+        &mut self,
+        checkpoint: &Checkpoint,
+        bots: &[CrawledBot],
+    ) -> Result<()> {
+        let file = match self.bots.take() {
+            Some(file) => file,
+            None => {
+                let path = bots_path(&self.dir, checkpoint.started_at);
+
+                jsonl::open::<CrawledBot>(&path)?.1
+            },
+        };
+        let file = self.bots.insert(file);
+        let bytes = append_lines(file, bots)?;
+        let saved = Saved {
+            bytes,
+            checkpoint: checkpoint.clone(),
+        };
+
+        let path = self.dir.join("checkpoint.json");
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, &saved)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        fs::rename(&tmp, path)?;
+
+        self.saved = Some((bytes, saved.checkpoint));
+
+        Ok(())
+    }
+
+    fn finish(&mut self, snapshot: &Snapshot) -> Result<()> {
+        append_lines(&self.snapshots_file, &[snapshot])?;
+        self.snapshots.push(snapshot.clone());
+
+        let path = self.dir.join("checkpoint.json");
+
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+
+        self.bots = None;
+        self.saved = None;
+
+        Ok(())
+    }
+}
+
+/// Appends values to a JSON-lines file, returning its new length.
+fn append_lines<T: Serialize>(file: &File, values: &[T]) -> Result<u64> {
+    let mut writer = BufWriter::new(file);
+
+    for value in values {
+        serde_json::to_writer(&mut writer, value)?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()?;
+    file.sync_data()?;
+
+    Ok(file.metadata()?.len())
+}
+
+fn bots_path(dir: &Path, started_at: DateTime<Utc>) -> PathBuf {
+    let name = started_at.format("bots-%Y%m%dT%H%M%S%.9fZ.jsonl");
+
+    dir.join(name.to_string())
+}
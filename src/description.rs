@@ -0,0 +1,839 @@
+//! Sanitization and conversion of bots' long descriptions.
+//!
+//! A bot's [long description] is untrusted user content which can contain
+//! HTML and/or Markdown. A [`Description`] detects the format, sanitizes it
+//! into safe HTML, and can convert it into plain text or Discord-flavoured
+//! Markdown.
+//!
+//! Scripts, styles, iframes, objects and tracking pixels are removed along
+//! with their content, as well as anything else that isn't safe to display.
+//!
+//! # Examples
+//!
+//! Convert a description into Markdown that fits in an embed field:
+//!
+//! ```rust
+//! use discord_bots_org::description::{Description, Format};
+//!
+//! let raw = "<p>A <b>fun</b> bot.</p><script>alert(1)</script>";
+//! let description = Description::new(raw);
+//!
+//! assert_eq!(description.format(), Format::Html);
+//! assert_eq!(description.html(None), "<p>A <b>fun</b> bot.</p>");
+//! assert_eq!(description.markdown(Some(1024)), "A **fun** bot.");
+//! assert_eq!(description.plain_text(Some(6)), "A fun…");
+//! ```
+//!
+//! [`Description`]: struct.Description.html
+//! [long description]: ../model/struct.Bot.html#structfield.description_long
+
+use ammonia::Builder;
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
+
+/// The detected format of a description.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Format {
+    /// The description is HTML, possibly containing some Markdown-like text.
+    Html,
+    /// The description is Markdown, possibly containing inline HTML.
+    Markdown,
+    /// The description contains no markup.
+    PlainText,
+}
+
+/// A sanitized description.
+///
+/// Refer to the [module-level documentation] for more information.
+///
+/// [module-level documentation]: index.html
+#[derive(Clone, Debug)]
+pub struct Description {
+    format: Format,
+    html: String,
+}
+
+impl Description {
+    /// Detects the format of a raw description and sanitizes it.
+    pub fn new(raw: impl AsRef<str>) -> Self {
+        Self::_new(raw.as_ref())
+    }
+
+    fn _new(raw: &str) -> Self {
+        let format = detect(raw);
+
+        let html = match format {
+            Format::Html => sanitize(raw),
+            Format::Markdown => {
+                let mut html = String::with_capacity(raw.len());
+                html::push_html(&mut html, Parser::new_ext(raw, options()));
+
+                sanitize(&html)
+            },
+            Format::PlainText => sanitize(&text_to_html(raw)),
+        };
+
+        Self {
+            format,
+            html,
+        }
+    }
+
+    /// The detected format of the raw description.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// The sanitized HTML of the description.
+    ///
+    /// If a limit is given, the text content is cut off with an ellipsis at
+    /// that many characters, and any open tags are closed. Markup does not
+    /// count towards the limit.
+    pub fn html(&self, limit: Option<usize>) -> String {
+        let limit = match limit {
+            Some(limit) => limit,
+            None => return self.html.clone(),
+        };
+
+        let total = Tokens::new(&self.html)
+            .map(|(_, token)| match token {
+                Token::Text(text) => chars(text).count(),
+                _ => 0,
+            })
+            .sum::<usize>();
+
+        if total <= limit {
+            return self.html.clone();
+        }
+
+        let mut count = 0;
+
+        for (offset, token) in Tokens::new(&self.html) {
+            let text = match token {
+                Token::Text(text) => text,
+                _ => continue,
+            };
+
+            for (char_offset, _) in chars(text) {
+                count += 1;
+
+                if count >= limit {
+                    let mut cut = self.html[..offset + char_offset].to_owned();
+
+                    if limit > 0 {
+                        cut.push('…');
+                    }
+
+                    return sanitize(&cut);
+                }
+            }
+        }
+
+        self.html.clone()
+    }
+
+    /// Converts the description into Discord-flavoured Markdown.
+    ///
+    /// If a limit is given, the output is cut off with an ellipsis so that it
+    /// is at most that many characters long, including formatting.
+    pub fn markdown(&self, limit: Option<usize>) -> String {
+        self.render(true, limit)
+    }
+
+    /// Converts the description into plain text.
+    ///
+    /// Links are followed by their URL in parentheses.
+    ///
+    /// If a limit is given, the output is cut off with an ellipsis so that it
+    /// is at most that many characters long.
+    pub fn plain_text(&self, limit: Option<usize>) -> String {
+        self.render(false, limit)
+    }
+
+    fn render(&self, markdown: bool, limit: Option<usize>) -> String {
+        let full = Renderer::new(markdown, None).render(&self.html);
+
+        match limit {
+            Some(limit) if full.chars().count() > limit => {
+                Renderer::new(markdown, Some(limit)).render(&self.html)
+            },
+            _ => full,
+        }
+    }
+}
+
+fn options() -> Options {
+    Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES
+}
+
+fn detect(raw: &str) -> Format {
+    if raw.trim_start().starts_with('<') && ammonia::is_html(raw) {
+        return Format::Html;
+    }
+
+    let mut has_html = false;
+
+    for event in Parser::new_ext(raw, options()) {
+        match event {
+            Event::Html(_) | Event::InlineHtml(_) => has_html = true,
+            Event::Start(Tag::Paragraph)
+            | Event::End(TagEnd::Paragraph)
+            | Event::Text(_)
+            | Event::SoftBreak => {},
+            _ => return Format::Markdown,
+        }
+    }
+
+    if has_html {
+        Format::Html
+    } else {
+        Format::PlainText
+    }
+}
+
+fn sanitize(html: &str) -> String {
+    let clean = Builder::default()
+        .add_clean_content_tags(&["iframe", "noscript", "object"])
+        .clean(html)
+        .to_string();
+
+    // Tracking pixels are removed after cleaning, when the markup is
+    // normalized.
+    let mut out = String::with_capacity(clean.len());
+
+    for (_, token) in Tokens::new(&clean) {
+        match token {
+            Token::Start { name: "img", attrs, .. }
+                if is_pixel(attr(attrs, "width"))
+                    || is_pixel(attr(attrs, "height")) => {},
+            token => out.push_str(token.raw()),
+        }
+    }
+
+    out
+}
+
+fn is_pixel(size: Option<String>) -> bool {
+    size.is_some_and(|size| {
+        let size = size.trim().trim_end_matches("px").trim();
+
+        size.parse::<f64>().is_ok_and(|size| size <= 1.0)
+    })
+}
+
+fn text_to_html(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| {
+            let escaped = paragraph
+                .trim()
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('\n', "<br>");
+
+            format!("<p>{}</p>", escaped)
+        })
+        .collect()
+}
+
+/// A token of normalized HTML, as output by `ammonia`.
+enum Token<'a> {
+    End {
+        name: &'a str,
+        raw: &'a str,
+    },
+    Start {
+        attrs: &'a str,
+        name: &'a str,
+        raw: &'a str,
+    },
+    Text(&'a str),
+}
+
+impl<'a> Token<'a> {
+    fn raw(&self) -> &'a str {
+        match self {
+            Token::End { raw, .. } | Token::Start { raw, .. } => raw,
+            Token::Text(text) => text,
+        }
+    }
+}
+
+/// An iterator over the tokens of normalized HTML, with their byte offsets.
+///
+/// This only supports the subset of HTML serialized by `ammonia`: comments
+/// are stripped, attribute values are always double quoted, and all text is
+/// escaped.
+struct Tokens<'a> {
+    html: &'a str,
+    offset: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(html: &'a str) -> Self {
+        Self {
+            html,
+            offset: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = (usize, Token<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.html[self.offset..];
+        let start = self.offset;
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        if !rest.starts_with('<') {
+            let len = rest.find('<').unwrap_or(rest.len());
+            self.offset += len;
+
+            return Some((start, Token::Text(&rest[..len])));
+        }
+
+        let mut quoted = false;
+        let len = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+
+                c == '>' && !quoted
+            })
+            .map_or(rest.len(), |(i, _)| i + 1);
+        self.offset += len;
+
+        let raw = &rest[..len];
+        let inner = raw.trim_start_matches('<').trim_end_matches('>');
+
+        let token = if let Some(name) = inner.strip_prefix('/') {
+            Token::End {
+                name,
+                raw,
+            }
+        } else {
+            let name_len = inner.find(' ').unwrap_or(inner.len());
+
+            Token::Start {
+                attrs: &inner[name_len..],
+                name: &inner[..name_len],
+                raw,
+            }
+        };
+
+        Some((start, token))
+    }
+}
+
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    let needle = format!(" {}=\"", name);
+    let start = attrs.find(&needle)? + needle.len();
+    let len = attrs[start..].find('"')?;
+
+    Some(unescape(&attrs[start..start + len]))
+}
+
+/// Percent-encodes the characters of a URL which would end a Markdown link's
+/// destination early or start its title.
+fn destination(href: &str) -> String {
+    let mut out = String::with_capacity(href.len());
+
+    for c in href.chars() {
+        if c.is_whitespace() || c.is_control() || "\"()<>\\".contains(c) {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                out.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+/// Iterates over the characters of escaped text with their byte offsets,
+/// treating each entity as a single character.
+fn chars(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        let rest = &text[offset..];
+        let c = rest.chars().next()?;
+        let start = offset;
+
+        if c == '&' {
+            if let Some(end) = rest.find(';').filter(|&end| end <= 10) {
+                offset += end + 1;
+
+                return Some((start, decode_entity(&rest[1..end])));
+            }
+        }
+
+        offset += c.len_utf8();
+
+        Some((start, c))
+    })
+}
+
+fn decode_entity(entity: &str) -> char {
+    let code = if let Some(hex) = entity
+        .strip_prefix("#x")
+        .or_else(|| entity.strip_prefix("#X"))
+    {
+        u32::from_str_radix(hex, 16).ok()
+    } else if let Some(decimal) = entity.strip_prefix('#') {
+        decimal.parse().ok()
+    } else {
+        None
+    };
+
+    match (entity, code) {
+        (_, Some(code)) => std::char::from_u32(code).unwrap_or('\u{fffd}'),
+        ("amp", _) => '&',
+        ("gt", _) => '>',
+        ("lt", _) => '<',
+        ("nbsp", _) => '\u{a0}',
+        ("quot", _) => '"',
+        _ => '\u{fffd}',
+    }
+}
+
+fn unescape(text: &str) -> String {
+    chars(text).map(|(_, c)| c).collect()
+}
+
+const VOID_TAGS: &[&str] = &["area", "br", "col", "hr", "img", "wbr"];
+
+/// Renders normalized HTML into plain text or Markdown.
+struct Renderer {
+    /// The text and closing Markdown of each open element.
+    elements: Vec<(String, String)>,
+    len: usize,
+    limit: Option<usize>,
+    lists: Vec<Option<u64>>,
+    markdown: bool,
+    newlines: usize,
+    out: String,
+    pre: usize,
+    quote: usize,
+    space: bool,
+    truncated: bool,
+}
+
+impl Renderer {
+    fn new(markdown: bool, limit: Option<usize>) -> Self {
+        Self {
+            elements: Vec::new(),
+            len: 0,
+            limit,
+            lists: Vec::new(),
+            markdown,
+            newlines: 0,
+            out: String::new(),
+            pre: 0,
+            quote: 0,
+            space: false,
+            truncated: false,
+        }
+    }
+
+    fn render(mut self, html: &str) -> String {
+        for (_, token) in Tokens::new(html) {
+            match token {
+                Token::End { name, .. } => self.end(name),
+                Token::Start { attrs, name, .. } => self.start(name, attrs),
+                Token::Text(text) => self.text(text),
+            }
+
+            if self.truncated {
+                break;
+            }
+        }
+
+        let trimmed = self.out.trim_end().len();
+        self.out.truncate(trimmed);
+
+        if self.truncated && self.limit != Some(0) {
+            self.out.push('…');
+        }
+
+        while let Some((_, closer)) = self.elements.pop() {
+            self.out.push_str(&closer);
+        }
+
+        self.out
+    }
+
+    fn closers_len(&self) -> usize {
+        self.elements.iter().map(|(_, c)| c.chars().count()).sum()
+    }
+
+    /// Whether the given number of characters can be written, leaving room for
+    /// closing any open elements and an ellipsis.
+    fn fits(&mut self, len: usize) -> bool {
+        let fits = self.limit.is_none_or(|limit| {
+            self.len + len + self.closers_len() < limit
+        });
+
+        if !fits {
+            self.truncated = true;
+        }
+
+        fits
+    }
+
+    fn write(&mut self, value: &str) -> bool {
+        let len = value.chars().count();
+
+        if !self.fits(len) {
+            return false;
+        }
+
+        self.out.push_str(value);
+        self.len += len;
+
+        true
+    }
+
+    /// Writes any pending whitespace before content.
+    fn flush(&mut self) -> bool {
+        if self.out.is_empty() {
+            self.newlines = 0;
+            self.space = false;
+        }
+
+        if self.newlines > 0 {
+            let prefix = if self.markdown && self.quote > 0 {
+                "> "
+            } else {
+                ""
+            };
+            let mut value = String::new();
+
+            for _ in 0..self.newlines {
+                value.push('\n');
+                value.push_str(prefix);
+            }
+
+            if !self.write(&value) {
+                return false;
+            }
+        } else if self.space && !self.write(" ") {
+            return false;
+        }
+
+        self.newlines = 0;
+        self.space = false;
+
+        true
+    }
+
+    fn at_line_start(&self) -> bool {
+        let out = &self.out;
+
+        out.is_empty() || out.ends_with('\n') || out.ends_with("> ")
+    }
+
+    fn block(&mut self, newlines: usize) {
+        self.newlines = self.newlines.max(newlines);
+        self.space = false;
+    }
+
+    fn start(&mut self, name: &str, attrs: &str) {
+        let (opener, closer) = match (name, self.markdown) {
+            ("a", markdown) => match attr(attrs, "href") {
+                Some(href) if markdown => {
+                    ("[", format!("]({})", destination(&href)))
+                },
+                Some(href) => ("", format!(" ({})", href)),
+                None => ("", String::new()),
+            },
+            ("b", true) | ("strong", true) => ("**", "**".to_owned()),
+            ("code", true) if self.pre == 0 => ("`", "`".to_owned()),
+            ("del", true) | ("s", true) | ("strike", true) => {
+                ("~~", "~~".to_owned())
+            },
+            ("em", true) | ("i", true) => ("*", "*".to_owned()),
+            ("ins", true) | ("u", true) => ("__", "__".to_owned()),
+            ("pre", true) => ("```\n", "\n```".to_owned()),
+            _ => ("", String::new()),
+        };
+
+        match name {
+            "blockquote" => {
+                self.block(2);
+                self.quote += 1;
+            },
+            "br" => {
+                self.newlines += 1;
+                self.space = false;
+            },
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "hr" | "p" | "table" => {
+                self.block(2);
+            },
+            "li" => {
+                self.block(1);
+            },
+            "ol" | "ul" => {
+                self.block(if self.lists.is_empty() { 2 } else { 1 });
+                self.lists.push(if name == "ol" { Some(1) } else { None });
+            },
+            "pre" => {
+                self.block(2);
+                self.pre += 1;
+            },
+            "dd" | "div" | "dt" | "tr" => self.block(1),
+            "td" | "th" => self.space = true,
+            _ => {},
+        }
+
+        let prefix = match name {
+            "h1" if self.markdown => "# ".to_owned(),
+            "h2" if self.markdown => "## ".to_owned(),
+            "h3" | "h4" | "h5" | "h6" if self.markdown => "### ".to_owned(),
+            "li" => {
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+
+                match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+
+                        format!("{}{}. ", indent, *number - 1)
+                    },
+                    _ if self.markdown => format!("{}- ", indent),
+                    _ => format!("{}• ", indent),
+                }
+            },
+            _ => String::new(),
+        };
+
+        let wrote_prefix =
+            prefix.is_empty() || self.flush() && self.write(&prefix);
+
+        if !wrote_prefix {
+            return;
+        }
+
+        if VOID_TAGS.contains(&name) {
+            return;
+        }
+
+        // The opener and closer are only written if both fit, as otherwise the
+        // element would be left unbalanced.
+        let closer_len = closer.chars().count();
+
+        if !opener.is_empty() {
+            if !(self.flush() && self.fits(opener.len() + closer_len)) {
+                return;
+            }
+
+            self.write(opener);
+        }
+
+        self.elements.push((name.to_owned(), closer));
+    }
+
+    fn end(&mut self, name: &str) {
+        let position = self.elements.iter().rposition(|(n, _)| n == name);
+
+        if let Some(position) = position {
+            while self.elements.len() > position {
+                if let Some((_, closer)) = self.elements.pop() {
+                    // The closer was reserved when the element was opened.
+                    self.out.push_str(&closer);
+                    self.len += closer.chars().count();
+                }
+            }
+        }
+
+        match name {
+            "blockquote" => {
+                self.quote = self.quote.saturating_sub(1);
+                self.block(2);
+            },
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "table" => {
+                self.block(2);
+            },
+            "ol" | "ul" => {
+                self.lists.pop();
+                self.block(if self.lists.is_empty() { 2 } else { 1 });
+            },
+            "pre" => {
+                self.pre = self.pre.saturating_sub(1);
+                self.block(2);
+            },
+            "dd" | "div" | "dt" | "li" | "tr" => self.block(1),
+            _ => {},
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let in_code = self.pre > 0
+            || self.elements.iter().any(|(name, _)| name == "code");
+
+        for (_, c) in chars(text) {
+            if self.pre > 0 {
+                if c == '\n' {
+                    // Newlines in preformatted text are kept as-is.
+                    if !(self.flush() && self.write("\n")) {
+                        return;
+                    }
+
+                    continue;
+                }
+            } else if c.is_whitespace() && c != '\u{a0}' {
+                self.space |= !self.at_line_start();
+
+                continue;
+            }
+
+            if !self.flush() {
+                return;
+            }
+
+            let escape = self.markdown
+                && !in_code
+                && (matches!(c, '\\' | '*' | '_' | '~' | '`' | '|')
+                    || (matches!(c, '>' | '#' | '-') && self.at_line_start()));
+
+            let mut value = String::with_capacity(2);
+
+            if escape {
+                value.push('\\');
+            }
+
+            value.push(c);
+
+            if !self.write(&value) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Description, Format};
+
+    #[test]
+    fn test_detect() {
+        let format = |raw| Description::new(raw).format();
+
+        assert_eq!(format("<div>A <b>bot</b></div>"), Format::Html);
+        assert_eq!(format("A <b>bot</b>."), Format::Html);
+        assert_eq!(format("# A bot\n\n**Fun**"), Format::Markdown);
+        assert_eq!(format("A bot.\nIt is fun."), Format::PlainText);
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let description = Description::new(
+            "<p onclick=\"x()\">Hi<script>alert(1)</script></p>\
+             <iframe src=\"https://example.com\">frame</iframe>\
+             <img src=\"https://example.com/1.gif\" width=\"1\" height=\"1\">\
+             <img src=\"https://example.com/logo.png\" width=\"64\">",
+        );
+
+        assert_eq!(
+            description.html(None),
+            "<p>Hi</p><img src=\"https://example.com/logo.png\" width=\"64\">",
+        );
+    }
+
+    #[test]
+    fn test_html_limit() {
+        let description = Description::new("<p><b>Hello</b> &amp; world</p>");
+
+        assert_eq!(description.html(Some(8)), "<p><b>Hello</b> &amp;…</p>");
+        assert_eq!(description.html(Some(100)), description.html(None));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let description = Description::new(
+            "<h1>Bot</h1>\
+             <p>A <strong>fun</strong> <a href=\"https://a.b/\">bot</a> \
+             with *stars*.</p><ul><li>One</li><li>Two</li></ul>\
+             <pre><code>!help\n!ping</code></pre>",
+        );
+
+        assert_eq!(
+            description.markdown(None),
+            "# Bot\n\n\
+             A **fun** [bot](https://a.b/) with \\*stars\\*.\n\n\
+             - One\n- Two\n\n\
+             ```\n!help\n!ping\n```",
+        );
+    }
+
+    #[test]
+    fn test_markdown_link() {
+        let description = Description::new(
+            "<p><a href=\"https://a.b/?q=&quot;x y&quot;)\">bot</a></p>",
+        );
+
+        assert_eq!(
+            description.markdown(None),
+            "[bot](https://a.b/?q=%22x%20y%22%29)",
+        );
+        assert_eq!(
+            description.plain_text(None),
+            "bot (https://a.b/?q=\"x y\"))",
+        );
+    }
+
+    #[test]
+    fn test_markdown_source() {
+        let description = Description::new("Use `!help`.\n\n1. *One*\n2. Two");
+
+        assert_eq!(description.format(), Format::Markdown);
+        assert_eq!(
+            description.markdown(None),
+            "Use `!help`.\n\n1. *One*\n2. Two",
+        );
+    }
+
+    #[test]
+    fn test_markdown_limit() {
+        let description = Description::new("<p>A <b>very fun</b> bot.</p>");
+
+        assert_eq!(description.markdown(Some(12)), "A **very…**");
+        assert_eq!(description.markdown(Some(20)), "A **very fun** bot.");
+        assert_eq!(description.markdown(Some(3)), "A…");
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let description = Description::new(
+            "<p>A <a href=\"https://a.b/\">bot</a>.</p>\n\
+             <p>Line<br>break</p>",
+        );
+
+        assert_eq!(
+            description.plain_text(None),
+            "A bot (https://a.b/).\n\nLine\nbreak",
+        );
+    }
+
+    #[test]
+    fn test_plain_text_source() {
+        let description = Description::new("A bot & friends > others.\nNew.");
+
+        assert_eq!(description.format(), Format::PlainText);
+        assert_eq!(
+            description.html(None),
+            "<p>A bot &amp; friends &gt; others.<br>New.</p>",
+        );
+        assert_eq!(
+            description.plain_text(None),
+            "A bot & friends > others.\nNew.",
+        );
+    }
+}
@@ -0,0 +1,485 @@
+//! Rendering of models into Discord message embeds.
+//!
+//! The [`Embed`] type serializes into the structure expected by Discord's API,
+//! so it can be sent as-is by any Discord library that accepts raw embed JSON.
+//!
+//! All text is truncated to Discord's embed limits.
+//!
+//! # Examples
+//!
+//! Render a bot into embed JSON:
+//!
+//! ```rust
+//! use discord_bots_org::{
+//!     embed::Embed,
+//!     model::{Bot, BotStats},
+//! };
+//!
+//! fn bot_info(bot: &Bot, stats: &BotStats) -> serde_json::Result<String> {
+//!     serde_json::to_string(&Embed::from_bot(bot, Some(stats)))
+//! }
+//! ```
+//!
+//! [`Embed`]: struct.Embed.html
+
+use crate::model::{Bot, BotStats, ImageFormat, User};
+use serde::{Deserialize, Serialize};
+
+/// The maximum length of an embed's title.
+pub const TITLE_LIMIT: usize = 256;
+/// The maximum length of an embed's description.
+pub const DESCRIPTION_LIMIT: usize = 4096;
+/// The maximum number of fields in an embed.
+pub const FIELD_COUNT_LIMIT: usize = 25;
+/// The maximum length of a field's name.
+pub const FIELD_NAME_LIMIT: usize = 256;
+/// The maximum length of a field's value.
+pub const FIELD_VALUE_LIMIT: usize = 1024;
+/// The maximum length of an embed's footer text.
+pub const FOOTER_LIMIT: usize = 2048;
+/// The maximum combined length of all text in an embed.
+pub const TOTAL_LIMIT: usize = 6000;
+
+/// The size of avatar thumbnails.
+const THUMBNAIL_SIZE: u16 = 256;
+
+/// A Discord message embed.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Embed {
+    /// The colour of the embed's side bar.
+    #[serde(rename = "color", skip_serializing_if = "Option::is_none")]
+    pub colour: Option<u32>,
+    /// The description of the embed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The fields of the embed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<EmbedField>,
+    /// The footer of the embed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer: Option<EmbedFooter>,
+    /// The large image of the embed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<EmbedImage>,
+    /// The thumbnail of the embed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<EmbedImage>,
+    /// The RFC 3339 timestamp of the embed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// The title of the embed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The URL that the title links to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl Embed {
+    /// Renders a bot, optionally including its statistics.
+    ///
+    /// The embed contains the bot's name, avatar, short description, points,
+    /// server count, prefix, library, owners, tags and links. Certified bots
+    /// are marked in the footer.
+    pub fn from_bot(bot: &Bot, stats: Option<&BotStats>) -> Self {
+        let mut embed = Self {
+            description: Some(bot.description_short.clone()),
+            thumbnail: Some(EmbedImage::new(
+                bot.avatar_url(THUMBNAIL_SIZE, ImageFormat::Gif),
+            )),
+            timestamp: Some(bot.date.to_rfc3339()),
+            title: Some(format!("{}#{}", bot.username, bot.discriminator)),
+            url: Some(bot.page_url()),
+            ..Self::default()
+        };
+
+        embed.push_field("Points", bot.points.to_string(), true);
+
+        if let Some(server_count) = stats.and_then(|s| s.server_count) {
+            embed.push_field("Servers", server_count.to_string(), true);
+        }
+
+        embed.push_field("Prefix", format!("`{}`", bot.prefix), true);
+        embed.push_field("Library", bot.lib.clone(), true);
+
+        if !bot.owners.is_empty() {
+            embed.push_field("Owners", mentions(&bot.owners), false);
+        }
+
+        if !bot.tags.is_empty() {
+            embed.push_field("Tags", bot.tags.join(", "), false);
+        }
+
+        let links = links(&[
+            ("Invite", bot.invite.clone()),
+            ("Website", bot.website.clone()),
+            ("Support", bot.support_url()),
+            ("GitHub", bot.github.clone()),
+        ]);
+
+        if !links.is_empty() {
+            embed.push_field("Links", links, false);
+        }
+
+        if bot.certified_bot {
+            embed.footer = Some(EmbedFooter::new("Certified bot"));
+        }
+
+        embed.truncate()
+    }
+
+    /// Renders a bot's statistics.
+    pub fn from_bot_stats(stats: &BotStats) -> Self {
+        let mut embed = Self {
+            title: Some("Statistics".to_owned()),
+            ..Self::default()
+        };
+
+        if let Some(server_count) = stats.server_count {
+            embed.push_field("Servers", server_count.to_string(), true);
+        }
+
+        if let Some(shard_count) = stats.shard_count {
+            embed.push_field("Shards", shard_count.to_string(), true);
+        }
+
+        if !stats.shards.is_empty() {
+            let shards = stats
+                .shards
+                .iter()
+                .enumerate()
+                .map(|(id, count)| format!("#{}: {}", id, count))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            embed.push_field("Servers per shard", shards, false);
+        }
+
+        embed.truncate()
+    }
+
+    /// Renders a user.
+    ///
+    /// The embed contains the user's name, avatar, banner, bio, colour, badges
+    /// and social links.
+    pub fn from_user(user: &User) -> Self {
+        let colour = user.colour.as_ref().and_then(|colour| {
+            u32::from_str_radix(colour.trim_start_matches('#'), 16).ok()
+        });
+
+        let mut embed = Self {
+            colour,
+            description: user.bio.clone().filter(|bio| !bio.is_empty()),
+            image: user.banner_url().map(EmbedImage::new),
+            thumbnail: Some(EmbedImage::new(
+                user.avatar_url(THUMBNAIL_SIZE, ImageFormat::Gif),
+            )),
+            title: Some(format!("{}#{}", user.username, user.discriminator)),
+            url: Some(user.page_url()),
+            ..Self::default()
+        };
+
+        let badges = [
+            ("Admin", user.admin),
+            ("Moderator", user.mod_),
+            ("Website moderator", user.web_mod),
+            ("Certified developer", user.certified_dev),
+            ("Supporter", user.supporter),
+        ]
+        .iter()
+        .filter(|(_, has)| *has)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+
+        if !badges.is_empty() {
+            embed.push_field("Badges", badges.join(", "), false);
+        }
+
+        let social = &user.social;
+        let links = links(&[
+            ("GitHub", social_url("github.com/", &social.github)),
+            ("Instagram", social_url("instagram.com/", &social.instagram)),
+            ("Reddit", social_url("reddit.com/user/", &social.reddit)),
+            ("Twitter", social_url("twitter.com/", &social.twitter)),
+            ("YouTube", social_url("youtube.com/", &social.youtube)),
+        ]);
+
+        if !links.is_empty() {
+            embed.push_field("Social", links, false);
+        }
+
+        embed.truncate()
+    }
+
+    /// Adds a field, if the embed has fewer than [`FIELD_COUNT_LIMIT`] fields.
+    ///
+    /// [`FIELD_COUNT_LIMIT`]: constant.FIELD_COUNT_LIMIT.html
+    pub fn push_field(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+        inline: bool,
+    ) -> &mut Self {
+        if self.fields.len() < FIELD_COUNT_LIMIT {
+            self.fields.push(EmbedField {
+                inline,
+                name: name.into(),
+                value: value.into(),
+            });
+        }
+
+        self
+    }
+
+    /// Truncates all text to Discord's limits.
+    ///
+    /// If the combined length of all text is still over [`TOTAL_LIMIT`], the
+    /// description is shortened further, followed by trailing fields being
+    /// removed.
+    ///
+    /// [`TOTAL_LIMIT`]: constant.TOTAL_LIMIT.html
+    pub fn truncate(mut self) -> Self {
+        truncate_opt(&mut self.title, TITLE_LIMIT);
+        truncate_opt(&mut self.description, DESCRIPTION_LIMIT);
+        self.fields.truncate(FIELD_COUNT_LIMIT);
+
+        for field in &mut self.fields {
+            truncate(&mut field.name, FIELD_NAME_LIMIT);
+            truncate(&mut field.value, FIELD_VALUE_LIMIT);
+        }
+
+        if let Some(footer) = self.footer.as_mut() {
+            truncate(&mut footer.text, FOOTER_LIMIT);
+        }
+
+        let excess = self.len().saturating_sub(TOTAL_LIMIT);
+
+        if let Some(description) = self.description.as_mut() {
+            let len = description.chars().count();
+            truncate(description, len.saturating_sub(excess));
+        }
+
+        while self.len() > TOTAL_LIMIT && self.fields.pop().is_some() {}
+
+        self
+    }
+
+    /// The combined length of all text in the embed, as counted by Discord.
+    pub fn len(&self) -> usize {
+        let len = |s: &Option<String>| {
+            s.as_ref().map_or(0, |s| s.chars().count())
+        };
+
+        len(&self.title)
+            + len(&self.description)
+            + self.footer.as_ref().map_or(0, |f| f.text.chars().count())
+            + self
+                .fields
+                .iter()
+                .map(|f| f.name.chars().count() + f.value.chars().count())
+                .sum::<usize>()
+    }
+
+    /// Whether the embed contains no text.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A field in a [`Embed`].
+///
+/// [`Embed`]: struct.Embed.html
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EmbedField {
+    /// Whether the field is displayed inline with other fields.
+    #[serde(default)]
+    pub inline: bool,
+    /// The name of the field.
+    pub name: String,
+    /// The value of the field.
+    pub value: String,
+}
+
+/// The footer of an [`Embed`].
+///
+/// [`Embed`]: struct.Embed.html
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EmbedFooter {
+    /// The text of the footer.
+    pub text: String,
+}
+
+impl EmbedFooter {
+    /// Creates a new footer with the given text.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+        }
+    }
+}
+
+/// An image or thumbnail of an [`Embed`].
+///
+/// [`Embed`]: struct.Embed.html
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EmbedImage {
+    /// The URL of the image.
+    pub url: String,
+}
+
+impl EmbedImage {
+    /// Creates a new image with the given URL.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+        }
+    }
+}
+
+fn links(links: &[(&str, Option<String>)]) -> String {
+    links
+        .iter()
+        .filter_map(|(name, url)| {
+            url.as_ref()
+                .filter(|url| !url.is_empty())
+                .map(|url| format!("[{}]({})", name, url))
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn mentions(ids: &[String]) -> String {
+    ids.iter()
+        .map(|id| format!("<@{}>", id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn social_url(base: &str, name: &str) -> Option<String> {
+    if name.is_empty() {
+        None
+    } else {
+        Some(format!("https://{}{}", base, name))
+    }
+}
+
+fn truncate(value: &mut String, limit: usize) {
+    if value.chars().count() <= limit {
+        return;
+    }
+
+    let mut truncated = value
+        .chars()
+        .take(limit.saturating_sub(1))
+        .collect::<String>();
+
+    if limit > 0 {
+        truncated.push('…');
+    }
+
+    *value = truncated;
+}
+
+fn truncate_opt(value: &mut Option<String>, limit: usize) {
+    if let Some(value) = value.as_mut() {
+        truncate(value, limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Bot, BotStats};
+    use serde_json::json;
+    use super::*;
+
+    fn bot() -> Bot {
+        serde_json::from_value(json!({
+            "avatar": "a_abc",
+            "certifiedBot": true,
+            "date": "2019-01-01T00:00:00.000Z",
+            "defAvatar": null,
+            "discriminator": "0001",
+            "github": "",
+            "id": "1",
+            "invite": null,
+            "lib": "serenity",
+            "longdesc": null,
+            "owners": ["2", "3"],
+            "points": 10,
+            "prefix": "!",
+            "shortdesc": "A bot.",
+            "support": "abc",
+            "tags": ["Fun", "Music"],
+            "username": "Bot",
+            "vanity": null,
+            "website": "https://example.com",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_bot() {
+        let stats = BotStats {
+            server_count: Some(100),
+            shards: vec![],
+            shard_count: None,
+        };
+        let embed = Embed::from_bot(&bot(), Some(&stats));
+
+        assert_eq!(embed.title.as_ref().unwrap(), "Bot#0001");
+        let url = embed.url.as_ref().unwrap();
+        assert_eq!(url, "https://discordbots.org/bot/1");
+        assert!(embed.thumbnail.as_ref().unwrap().url.contains("a_abc.gif"));
+        assert_eq!(embed.footer.as_ref().unwrap().text, "Certified bot");
+
+        let field = |name| {
+            embed
+                .fields
+                .iter()
+                .find(|f| f.name == name)
+                .map(|f| f.value.as_str())
+        };
+        assert_eq!(field("Points"), Some("10"));
+        assert_eq!(field("Servers"), Some("100"));
+        assert_eq!(field("Owners"), Some("<@2>, <@3>"));
+        assert_eq!(field("Tags"), Some("Fun, Music"));
+        assert_eq!(
+            field("Links"),
+            Some(
+                "[Website](https://example.com) | \
+                 [Support](https://discord.gg/abc)",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut bot = bot();
+        bot.username = "a".repeat(300);
+        bot.description_short = "b".repeat(5000);
+        bot.tags = vec!["c".repeat(2000)];
+
+        let embed = Embed::from_bot(&bot, None);
+        let title = embed.title.as_ref().unwrap();
+
+        assert_eq!(title.chars().count(), TITLE_LIMIT);
+        assert!(title.ends_with('…'));
+        assert_eq!(embed.description.as_ref().unwrap().chars().count(), 4096);
+        assert!(embed.fields.iter().all(|f| f.value.chars().count() <= 1024));
+        assert!(embed.len() <= TOTAL_LIMIT);
+    }
+
+    #[test]
+    fn test_serialize_skips_empty() {
+        let embed = Embed::from_bot_stats(&BotStats {
+            server_count: None,
+            shards: vec![],
+            shard_count: None,
+        });
+
+        assert_eq!(
+            serde_json::to_value(&embed).unwrap(),
+            json!({ "title": "Statistics" }),
+        );
+    }
+}
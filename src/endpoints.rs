@@ -1,84 +1,90 @@
-// This module can be "dead" if no features are enabled.
-#![cfg_attr(not(any(
-    feature = "reqwest-async-support",
-    feature = "reqwest-sync-support"
-)), allow(dead_code))]
-
 /// API URI base.
-const BASE: &str = "https://discordbots.org/api";
+pub const BASE: &str = "https://discordbots.org/api";
+
+/// Discord CDN URI base.
+const CDN: &str = "https://cdn.discordapp.com";
+
+/// Website URI base.
+const SITE: &str = "https://discordbots.org";
 
-pub fn bot(id: u64) -> String {
-    format!("{}/bots/{}", BASE, id)
+pub fn widget(id: u64) -> String {
+    format!("{}/widget/{}.svg", BASE, id)
 }
 
-pub fn bot_stats(id: u64) -> String {
-    format!("{}/bots/{}/stats", BASE, id)
+pub fn png_widget(id: u64) -> String {
+    format!("{}/widget/{}.png", BASE, id)
 }
 
-pub fn bot_vote_check(bot_id: u64, user_id: u64) -> String {
-    format!("{}/bots/{}/check?userId={}", BASE, bot_id, user_id)
+pub fn avatar(id: &str, hash: &str, ext: &str, size: u16) -> String {
+    format!("{}/avatars/{}/{}.{}?size={}", CDN, id, hash, ext, size)
 }
 
-pub fn bot_votes(id: u64) -> String {
-    format!("{}/bots/{}/votes", BASE, id)
+pub fn default_avatar(index: u16) -> String {
+    format!("{}/embed/avatars/{}.png", CDN, index)
 }
 
-pub fn bots() -> String {
-    format!("{}/bots", BASE)
+pub fn bot_page(slug: &str) -> String {
+    format!("{}/bot/{}", SITE, slug)
 }
 
-pub fn user(id: u64) -> String {
-    format!("{}/users/{}", BASE, id)
+pub fn vote_page(id: u64) -> String {
+    format!("{}/bot/{}/vote", SITE, id)
 }
 
-pub fn widget(id: u64) -> String {
-    format!("{}/widget/{}.svg", BASE, id)
+pub fn user_page(id: &str) -> String {
+    format!("{}/user/{}", SITE, id)
 }
 
-pub fn png_widget(id: u64) -> String {
-    format!("{}/widget/{}.png", BASE, id)
+pub fn support_invite(code: &str) -> String {
+    format!("https://discord.gg/{}", code)
 }
 
+pub fn oauth2_authorize() -> String {
+    "https://discord.com/oauth2/authorize".to_owned()
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_bot() {
-        assert_eq!(bot(1), "https://discordbots.org/api/bots/1");
+    fn test_widget() {
+        assert_eq!(widget(1), "https://discordbots.org/api/widget/1.svg");
     }
 
     #[test]
-    fn test_bot_stats() {
-        assert_eq!(bot_stats(1), "https://discordbots.org/api/bots/1/stats");
+    fn test_avatar() {
+        assert_eq!(
+            avatar("1", "abc", "png", 128),
+            "https://cdn.discordapp.com/avatars/1/abc.png?size=128",
+        );
     }
 
     #[test]
-    fn test_bot_vote_check() {
+    fn test_default_avatar() {
         assert_eq!(
-            bot_vote_check(1, 2),
-            "https://discordbots.org/api/bots/1/check?userId=2",
+            default_avatar(3),
+            "https://cdn.discordapp.com/embed/avatars/3.png",
         );
     }
 
     #[test]
-    fn test_bot_votes() {
-        assert_eq!(bot_votes(1), "https://discordbots.org/api/bots/1/votes");
+    fn test_bot_page() {
+        assert_eq!(bot_page("1"), "https://discordbots.org/bot/1");
     }
 
     #[test]
-    fn test_bots() {
-        assert_eq!(bots(), "https://discordbots.org/api/bots");
+    fn test_user_page() {
+        assert_eq!(user_page("1"), "https://discordbots.org/user/1");
     }
 
     #[test]
-    fn test_user() {
-        assert_eq!(user(1), "https://discordbots.org/api/users/1");
+    fn test_vote_page() {
+        assert_eq!(vote_page(1), "https://discordbots.org/bot/1/vote");
     }
 
     #[test]
-    fn test_widget() {
-        assert_eq!(widget(1), "https://discordbots.org/api/widget/1.svg");
+    fn test_support_invite() {
+        assert_eq!(support_invite("abc"), "https://discord.gg/abc");
     }
 }
@@ -1,15 +1,24 @@
+use crate::bridge::operation::Response;
 use serde_json::Error as JsonError;
 use std::error::Error as StdError;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Error as IoError;
+use std::num::ParseIntError;
 use std::result::Result as StdResult;
 use url::ParseError as UrlParseError;
 
+#[cfg(feature = "hyper-support")]
+use http::Error as HttpError;
+#[cfg(feature = "hyper-support")]
+use hyper::Error as HyperBodyError;
+#[cfg(feature = "hyper-support")]
+use hyper_util::client::legacy::Error as HyperError;
+#[cfg(feature = "ureq-support")]
+use ureq::Error as UreqError;
 #[cfg(feature = "reqwest")]
-use reqwest::{
-    Error as ReqwestError,
-    Response as ReqwestResponse,
-    header::InvalidHeaderValue,
-};
+use reqwest::{Error as ReqwestError, header::InvalidHeaderValue};
+#[cfg(feature = "reqwest-sync-support")]
+use reqwest::blocking::Response as ReqwestResponse;
 
 /// A result type to compose a successful value and the library's [`Error`]
 /// type.
@@ -21,8 +30,25 @@ pub type Result<T> = StdResult<T, Error>;
 /// errors.
 #[derive(Debug)]
 pub enum Error {
+    /// An error from the `hyper` crate when sending a request.
+    #[cfg(feature = "hyper-support")]
+    Hyper(HyperError),
+    /// An error from the `hyper` crate when receiving a response body.
+    #[cfg(feature = "hyper-support")]
+    HyperBody(HyperBodyError),
+    /// An error when building a request for `hyper`, such as when a header
+    /// value is invalid.
+    #[cfg(feature = "hyper-support")]
+    HyperRequest(HttpError),
+    /// When an ID is not a valid integer.
+    InvalidId(ParseIntError),
+    /// When an OAuth2 invite URL is not a Discord authorization URL or is
+    /// missing a valid client ID.
+    InvalidInvite,
     /// When a URL is invalid.
     InvalidUrl(UrlParseError),
+    /// An I/O error, such as when reading or writing a file.
+    Io(IoError),
     /// An error from the `serde_json` crate.
     ///
     /// A potential reason for this is when there is an error deserializing a
@@ -32,17 +58,45 @@ pub enum Error {
     #[cfg(feature = "reqwest")]
     Reqwest(ReqwestError),
     /// An error indicating a bad request when using `reqwest`.
-    #[cfg(feature = "reqwest")]
+    #[cfg(feature = "reqwest-sync-support")]
     ReqwestBad(Box<ReqwestResponse>),
     /// An error indicating that a header value was invalid.
     #[cfg(feature = "reqwest")]
     ReqwestHeaderValue(InvalidHeaderValue),
     /// An error indicating an invalid request when using `reqwest`.
-    #[cfg(feature = "reqwest")]
+    #[cfg(feature = "reqwest-sync-support")]
     ReqwestInvalid(Box<ReqwestResponse>),
     /// An error indicating an unathorized request when using `reqwest`.
-    #[cfg(feature = "reqwest")]
+    #[cfg(feature = "reqwest-sync-support")]
     ReqwestUnauthorized(Box<ReqwestResponse>),
+    /// An error indicating that the API responded with an unsuccessful
+    /// status code.
+    Status(Response),
+    /// An error from the `ureq` crate, such as when a request fails or
+    /// the response has an unsuccessful status code.
+    #[cfg(feature = "ureq-support")]
+    Ureq(UreqError),
+}
+
+#[cfg(feature = "hyper-support")]
+impl From<HttpError> for Error {
+    fn from(err: HttpError) -> Self {
+        Error::HyperRequest(err)
+    }
+}
+
+#[cfg(feature = "hyper-support")]
+impl From<HyperBodyError> for Error {
+    fn from(err: HyperBodyError) -> Self {
+        Error::HyperBody(err)
+    }
+}
+
+#[cfg(feature = "hyper-support")]
+impl From<HyperError> for Error {
+    fn from(err: HyperError) -> Self {
+        Error::Hyper(err)
+    }
 }
 
 #[cfg(feature = "reqwest")]
@@ -52,13 +106,18 @@ impl From<InvalidHeaderValue> for Error {
     }
 }
 
+impl From<IoError> for Error {
+    fn from(err: IoError) -> Self {
+        Error::Io(err)
+    }
+}
+
 impl From<JsonError> for Error {
     fn from(err: JsonError) -> Self {
         Error::Json(err)
     }
 }
 
-#[cfg(feature = "reqwest")]
 impl From<UrlParseError> for Error {
     fn from(err: UrlParseError) -> Self {
         Error::InvalidUrl(err)
@@ -72,27 +131,66 @@ impl From<ReqwestError> for Error {
     }
 }
 
+#[cfg(feature = "ureq-support")]
+impl From<UreqError> for Error {
+    fn from(err: UreqError) -> Self {
+        Error::Ureq(err)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        f.write_str(self.description())
+        match self {
+            #[cfg(feature = "hyper-support")]
+            Error::Hyper(e) => Display::fmt(e, f),
+            #[cfg(feature = "hyper-support")]
+            Error::HyperBody(e) => Display::fmt(e, f),
+            #[cfg(feature = "hyper-support")]
+            Error::HyperRequest(e) => Display::fmt(e, f),
+            Error::InvalidId(e) => Display::fmt(e, f),
+            Error::InvalidInvite => f.write_str("Invite URL invalid"),
+            Error::InvalidUrl(e) => Display::fmt(e, f),
+            Error::Io(e) => Display::fmt(e, f),
+            Error::Json(e) => Display::fmt(e, f),
+            #[cfg(feature = "reqwest")]
+            Error::Reqwest(e) => Display::fmt(e, f),
+            #[cfg(feature = "reqwest-sync-support")]
+            Error::ReqwestBad(_) => f.write_str("Request bad"),
+            #[cfg(feature = "reqwest")]
+            Error::ReqwestHeaderValue(e) => Display::fmt(e, f),
+            #[cfg(feature = "reqwest-sync-support")]
+            Error::ReqwestInvalid(_) => f.write_str("Request invalid"),
+            #[cfg(feature = "reqwest-sync-support")]
+            Error::ReqwestUnauthorized(_) => f.write_str("Request auth bad"),
+            Error::Status(resp) => {
+                write!(f, "Request unsuccessful: {}", resp.status)
+            },
+            #[cfg(feature = "ureq-support")]
+            Error::Ureq(e) => Display::fmt(e, f),
+        }
     }
 }
 
 impl StdError for Error {
-    fn description(&self) -> &str {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
-            Error::InvalidUrl(e) => e.description(),
-            Error::Json(e) => e.description(),
-            #[cfg(feature = "reqwest")]
-            Error::Reqwest(e) => e.description(),
-            #[cfg(feature = "reqwest")]
-            Error::ReqwestBad(_) => "Request bad",
-            #[cfg(feature = "reqwest")]
-            Error::ReqwestHeaderValue(e) => e.description(),
+            #[cfg(feature = "hyper-support")]
+            Error::Hyper(e) => Some(e),
+            #[cfg(feature = "hyper-support")]
+            Error::HyperBody(e) => Some(e),
+            #[cfg(feature = "hyper-support")]
+            Error::HyperRequest(e) => Some(e),
+            Error::InvalidId(e) => Some(e),
+            Error::InvalidUrl(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
             #[cfg(feature = "reqwest")]
-            Error::ReqwestInvalid(_) => "Request invalid",
+            Error::Reqwest(e) => Some(e),
             #[cfg(feature = "reqwest")]
-            Error::ReqwestUnauthorized(_) => "Request auth bad",
+            Error::ReqwestHeaderValue(e) => Some(e),
+            #[cfg(feature = "ureq-support")]
+            Error::Ureq(e) => Some(e),
+            _ => None,
         }
     }
 }
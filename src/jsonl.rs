@@ -0,0 +1,48 @@
+//! Reading of append-only JSON-lines files.
+
+use crate::Result;
+use serde::de::DeserializeOwned;
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// Opens a JSON-lines file for appending, creating it if it doesn't exist,
+/// and reads the values in it, skipping blank lines.
+///
+/// A final line without a newline, such as one torn by a crash while it was
+/// being appended, is truncated away. Any other invalid line is an error.
+pub fn open<T: DeserializeOwned>(path: &Path) -> Result<(Vec<T>, File)> {
+    let file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .read(true)
+        .open(path)?;
+    let mut reader = BufReader::new(&file);
+    let mut line = Vec::new();
+    let mut len = 0;
+    let mut values = Vec::new();
+
+    loop {
+        line.clear();
+
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+
+        if line.last() != Some(&b'\n') {
+            file.set_len(len)?;
+
+            break;
+        }
+
+        len += line.len() as u64;
+
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            values.push(serde_json::from_slice(&line)?);
+        }
+    }
+
+    Ok((values, file))
+}
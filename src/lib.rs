@@ -1,19 +1,27 @@
 //! [![travis-badge][]][travis] [![license-badge][]][license] [![docs-badge][]][docs] [![rust badge]][rust link]
 //!
 //! An unofficial Rust library acting as a wrapper around the [Discord Bot List]
-//! API, offering implementations for both sync and async reqwest (v0.9).
+//! API, offering implementations for both sync and async reqwest (v0.12).
 //!
 //! ### Compile features
 //!
 //! - **reqwest-sync-support**: Compliles with sync `reqwest` support (*default*)
 //! - **reqwest-async-support**: Compiles with async `reqwest` support
-//!
-//! Note that `reqwest-async-support` requires nightly for the unstable
-//! `core::future` API.
+//! - **cli**: Builds the `dbl` command-line tool and the `dbl-proxy` caching
+//!   reverse proxy on sync `reqwest` support
+//! - **description**: Compiles with sanitization and conversion of bots' long
+//!   descriptions
+//! - **embed**: Compiles with rendering of models into Discord embeds
+//! - **hyper-support**: Compiles with an async client on a caller-supplied
+//!   `hyper` client, without `reqwest`
+//! - **tower-support**: Compiles with a `tower` service and layer for receiving
+//!   webhooks
+//! - **ureq-support**: Compiles with a lightweight sync client on `ureq`,
+//!   without `reqwest`
 //!
 //! ### Installation
 //!
-//! This library requires at least Rust 1.31.0.
+//! This library requires at least Rust 1.88.0.
 //!
 //! Add the following to your `Cargo.toml` file:
 //!
@@ -48,7 +56,7 @@
 //! extern crate reqwest;
 //!
 //! use discord_bots_org::ReqwestSyncClient as ApiClient;
-//! use reqwest::Client as ReqwestClient;
+//! use reqwest::blocking::Client as ReqwestClient;
 //! use std::{
 //!     error::Error,
 //!     sync::Arc,
@@ -70,8 +78,8 @@
 //! }
 //! ```
 //!
-//! Examples are sparse for asynchronous reqwest. It is assumed that if you're using
-//! unstable asynchronous Rust APIs that you're already proficient in them.
+//! The async client has the same methods, returning futures which can be run by
+//! any executor.
 //!
 //! For more examples, refer to the [examples] folder.
 //!
@@ -87,22 +95,43 @@
 //! [examples]: https://github.com/zeyla/discord-bots-org.rs/tree/master/examples
 //! [license]: https://github.com/zeyla/discord-bots-org.rs/blob/master/LICENSE.md
 //! [license-badge]: https://img.shields.io/badge/license-ISC-blue.svg?style=flat-square
-//! [rust badge]: https://img.shields.io/badge/rust-1.31.0+-93450a.svg?style=flat-square
-//! [rust link]: https://blog.rust-lang.org/2018/12/06/Rust-1.31-and-rust-2018.html
+//! [rust badge]: https://img.shields.io/badge/rust-1.88.0+-93450a.svg?style=flat-square
+//! [rust link]: https://blog.rust-lang.org/2025/06/26/Rust-1.88.0/
 //! [travis]: https://travis-ci.org/zeyla/discord-bots-org.rs
 //! [travis-badge]: https://img.shields.io/travis/zeyla/discord-bots-org.rs.svg?style=flat-square
 #![deny(missing_docs)]
-#![cfg_attr(
-    feature = "futures-preview",
-    feature(async_await, await_macro, futures_api)
-)]
 
 pub mod bridge;
 pub mod builder;
+pub mod crawl;
 pub mod model;
+pub mod route;
+pub mod vote;
+pub mod webhook;
+
+#[cfg(feature = "description")]
+pub mod description;
+#[cfg(feature = "embed")]
+pub mod embed;
 
 mod endpoints;
 mod error;
+#[cfg(all(
+    test,
+    any(feature = "reqwest-async-support", feature = "tower-support"),
+))]
+mod executor;
+mod jsonl;
+#[cfg(all(
+    test,
+    any(
+        feature = "hyper-support",
+        feature = "reqwest-async-support",
+        feature = "reqwest-sync-support",
+        feature = "ureq-support",
+    ),
+))]
+mod mock;
 
 pub use self::error::{Error, Result};
 
@@ -0,0 +1,156 @@
+//! A minimal HTTP server for testing clients against canned responses.
+
+// Not every helper is used by the tests of every feature.
+#![allow(dead_code)]
+
+use crate::route::Route;
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+
+/// A request received by a [`MockServer`].
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    pub authorization: Option<String>,
+    pub body: String,
+    pub method: String,
+    pub path: String,
+    pub received_at: Instant,
+}
+
+/// An HTTP server on a local port, responding to requests by their method and
+/// path.
+///
+/// Requests without a route are responded to with a 404.
+pub struct MockServer {
+    port: u16,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    /// Starts a server responding to routes with a status code and body.
+    pub fn start(routes: &[(Route, u16, &str)]) -> Self {
+        let routes = routes
+            .iter()
+            .map(|(route, status, body)| {
+                let method = route.method().as_str();
+
+                (key(method, &route.path()), (*status, body.to_string()))
+            })
+            .collect();
+
+        Self::serve(routes)
+    }
+
+    /// Starts a server responding to methods and paths, such as
+    /// `("POST", "/votes")`, with a status code and body.
+    pub fn start_paths(paths: &[(&str, &str, u16, &str)]) -> Self {
+        let paths = paths
+            .iter()
+            .map(|(method, path, status, body)| {
+                (key(method, path), (*status, body.to_string()))
+            })
+            .collect();
+
+        Self::serve(paths)
+    }
+
+    fn serve(routes: HashMap<String, (u16, String)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let received = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let request = match read_request(&stream) {
+                    Some(request) => request,
+                    None => continue,
+                };
+                let path = request.path.split('?').next().unwrap_or_default();
+                let (status, body) = routes
+                    .get(&key(&request.method, path))
+                    .cloned()
+                    .unwrap_or((404, String::new()));
+
+                received.lock().unwrap().push(request);
+                write_response(stream, status, &body);
+            }
+        });
+
+        Self {
+            port,
+            requests,
+        }
+    }
+
+    /// The base URL of the server, such as `http://127.0.0.1:1234`.
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// The requests received so far, in order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn key(method: &str, path: &str) -> String {
+    format!("{} {}", method, path)
+}
+
+fn read_request(stream: &TcpStream) -> Option<MockRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+
+    let received_at = Instant::now();
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_owned();
+    let path = parts.next()?.to_owned();
+    let mut authorization = None;
+    let mut length = 0;
+
+    loop {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+
+        let (name, value) = match line.trim_end().split_once(':') {
+            Some((name, value)) => (name.to_ascii_lowercase(), value.trim()),
+            None => break,
+        };
+
+        match name.as_str() {
+            "authorization" => authorization = Some(value.to_owned()),
+            "content-length" => length = value.parse().ok()?,
+            _ => {},
+        }
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(MockRequest {
+        authorization,
+        body: String::from_utf8(body).ok()?,
+        method,
+        path,
+        received_at,
+    })
+}
+
+fn write_response(mut stream: TcpStream, status: u16, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body,
+    );
+}
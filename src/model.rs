@@ -1,7 +1,11 @@
 //! Models mapping the Discord Bot List API.
 
+use crate::endpoints;
+#[cfg(feature = "description")]
+use crate::description::Description;
 use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
+use url::form_urlencoded;
 
 /// Information about a bot.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -51,6 +55,54 @@ pub struct Bot {
     pub website: Option<String>,
 }
 
+impl Bot {
+    /// Creates a URL to the bot's avatar.
+    ///
+    /// Falls back to the bot's [default avatar] if it has no avatar set. Refer
+    /// to [`ImageFormat`] for how animated avatars are handled.
+    ///
+    /// The size is rounded up to the nearest power of two between 16 and 4096.
+    ///
+    /// [`ImageFormat`]: enum.ImageFormat.html
+    /// [default avatar]: #method.default_avatar_url
+    pub fn avatar_url(&self, size: u16, format: ImageFormat) -> String {
+        let discriminator = self.discriminator.parse().unwrap_or(0);
+
+        avatar_url(&self.id, self.avatar.as_ref(), discriminator, size, format)
+    }
+
+    /// Creates a URL to the default avatar Discord assigns based on the bot's
+    /// discriminator.
+    pub fn default_avatar_url(&self) -> String {
+        let discriminator = self.discriminator.parse::<u16>().unwrap_or(0);
+
+        endpoints::default_avatar(discriminator % 5)
+    }
+
+    /// Creates a URL to the bot's page on the website.
+    ///
+    /// This uses the bot's vanity slug if it has one, and its ID otherwise.
+    pub fn page_url(&self) -> String {
+        self.vanity_url().unwrap_or_else(|| endpoints::bot_page(&self.id))
+    }
+
+    /// Creates a URL to the bot's vanity page on the website, if it has one.
+    pub fn vanity_url(&self) -> Option<String> {
+        non_empty(self.vanity.as_ref()).map(endpoints::bot_page)
+    }
+
+    /// Creates an invite URL to the bot's support server, if it has one.
+    pub fn support_url(&self) -> Option<String> {
+        non_empty(self.support.as_ref()).map(endpoints::support_invite)
+    }
+
+    /// Sanitizes the bot's long description, if it has one.
+    #[cfg(feature = "description")]
+    pub fn long_description(&self) -> Option<Description> {
+        non_empty(self.description_long.as_ref()).map(Description::new)
+    }
+}
+
 /// Information about a bot's statistics.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -89,11 +141,71 @@ pub struct DiscordUser {
     pub username: String,
 }
 
+impl DiscordUser {
+    /// Creates a URL to the user's avatar.
+    ///
+    /// Falls back to the user's [default avatar] if they have no avatar set.
+    /// Refer to [`ImageFormat`] for how animated avatars are handled.
+    ///
+    /// The size is rounded up to the nearest power of two between 16 and 4096.
+    ///
+    /// [`ImageFormat`]: enum.ImageFormat.html
+    /// [default avatar]: #method.default_avatar_url
+    pub fn avatar_url(&self, size: u16, format: ImageFormat) -> String {
+        let avatar = self.avatar.as_ref();
+
+        avatar_url(&self.id, avatar, self.discriminator, size, format)
+    }
+
+    /// Creates a URL to the default avatar Discord assigns based on the user's
+    /// discriminator.
+    pub fn default_avatar_url(&self) -> String {
+        endpoints::default_avatar(self.discriminator % 5)
+    }
+}
+
+/// The format of an image retrieved from Discord's CDN.
+///
+/// Animated avatars have a hash prefixed with `a_`. Requesting a [`Gif`] of
+/// an animated avatar retrieves the animation, while requesting a [`Gif`] of
+/// a static avatar falls back to a [`Png`]. Any other format retrieves a
+/// static image.
+///
+/// [`Gif`]: #variant.Gif
+/// [`Png`]: #variant.Png
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ImageFormat {
+    /// A GIF image, animated if the avatar is.
+    Gif,
+    /// A JPEG image.
+    Jpeg,
+    /// A PNG image.
+    Png,
+    /// A WebP image.
+    WebP,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Gif => "gif",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::WebP => "webp",
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub(crate) struct ResponseUserVoted {
     pub voted: u8,
 }
 
+#[derive(Deserialize)]
+pub(crate) struct ResponseWeekend {
+    pub is_weekend: bool,
+}
+
 /// Information about a search response.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -193,6 +305,41 @@ pub struct User {
     pub web_mod: bool,
 }
 
+impl User {
+    /// Creates a URL to the user's avatar.
+    ///
+    /// Falls back to the user's [default avatar] if they have no avatar set.
+    /// Refer to [`ImageFormat`] for how animated avatars are handled.
+    ///
+    /// The size is rounded up to the nearest power of two between 16 and 4096.
+    ///
+    /// [`ImageFormat`]: enum.ImageFormat.html
+    /// [default avatar]: #method.default_avatar_url
+    pub fn avatar_url(&self, size: u16, format: ImageFormat) -> String {
+        let discriminator = self.discriminator.parse().unwrap_or(0);
+
+        avatar_url(&self.id, self.avatar.as_ref(), discriminator, size, format)
+    }
+
+    /// Creates a URL to the default avatar Discord assigns based on the user's
+    /// discriminator.
+    pub fn default_avatar_url(&self) -> String {
+        let discriminator = self.discriminator.parse::<u16>().unwrap_or(0);
+
+        endpoints::default_avatar(discriminator % 5)
+    }
+
+    /// The URL of the user's banner image, if they have one.
+    pub fn banner_url(&self) -> Option<&str> {
+        non_empty(self.banner.as_ref())
+    }
+
+    /// Creates a URL to the user's page on the website.
+    pub fn page_url(&self) -> String {
+        endpoints::user_page(&self.id)
+    }
+}
+
 /// Information about an incoming webhook.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -217,6 +364,45 @@ pub struct Webhook {
     pub user_id: String,
 }
 
+impl Webhook {
+    /// Parses the query param string into decoded key/value pairs, in the
+    /// order they appear.
+    ///
+    /// A leading `?` is ignored, and repeated keys are all included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use discord_bots_org::model::{Webhook, WebhookType};
+    /// # let webhook = Webhook {
+    /// #     bot: "1".to_owned(),
+    /// #     is_weekend: false,
+    /// #     kind: WebhookType::Upvote,
+    /// #     query: "?campaign=spring%20sale&ref=a&ref=b".to_owned(),
+    /// #     user_id: "2".to_owned(),
+    /// # };
+    /// let params = webhook.query_params();
+    ///
+    /// assert_eq!(params[0].0, "campaign");
+    /// assert_eq!(params[0].1, "spring sale");
+    /// assert_eq!(params.len(), 3);
+    /// ```
+    pub fn query_params(&self) -> Vec<(String, String)> {
+        let query = self.query.trim_start_matches('?');
+
+        form_urlencoded::parse(query.as_bytes()).into_owned().collect()
+    }
+
+    /// The decoded value of the first query param with the given key, if
+    /// any.
+    pub fn query_param(&self, key: &str) -> Option<String> {
+        self.query_params()
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+}
+
 /// The type of webhook that was received.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -226,3 +412,183 @@ pub enum WebhookType {
     /// Indicator that this is a "normal" webhook, i.e. non-testing.
     Upvote,
 }
+
+fn avatar_url(
+    id: &str,
+    hash: Option<&String>,
+    discriminator: u16,
+    size: u16,
+    format: ImageFormat,
+) -> String {
+    let hash = match non_empty(hash) {
+        Some(hash) => hash,
+        None => return endpoints::default_avatar(discriminator % 5),
+    };
+
+    let format = match format {
+        ImageFormat::Gif if !hash.starts_with("a_") => ImageFormat::Png,
+        other => other,
+    };
+    let size = size.clamp(16, 4096).next_power_of_two();
+
+    endpoints::avatar(id, hash, format.extension(), size)
+}
+
+fn non_empty(value: Option<&String>) -> Option<&str> {
+    value.map(String::as_str).filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::{Bot, DiscordUser, ImageFormat, User, Webhook, WebhookType};
+
+    fn bot(vanity: Option<&str>, support: Option<&str>) -> Bot {
+        serde_json::from_value(json!({
+            "avatar": null,
+            "certifiedBot": false,
+            "date": "2019-01-01T00:00:00.000Z",
+            "defAvatar": null,
+            "discriminator": "0002",
+            "id": "1",
+            "lib": "serenity",
+            "owners": ["2"],
+            "points": 10,
+            "prefix": "!",
+            "shortdesc": "A bot.",
+            "support": support,
+            "tags": [],
+            "username": "test",
+            "vanity": vanity,
+        }))
+        .unwrap()
+    }
+
+    fn user(avatar: Option<&str>) -> DiscordUser {
+        DiscordUser {
+            avatar: avatar.map(ToOwned::to_owned),
+            discriminator: 1337,
+            id: "1".to_owned(),
+            username: "test".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_avatar_url() {
+        assert_eq!(
+            user(Some("abc")).avatar_url(128, ImageFormat::WebP),
+            "https://cdn.discordapp.com/avatars/1/abc.webp?size=128",
+        );
+    }
+
+    #[test]
+    fn test_avatar_url_animated() {
+        assert_eq!(
+            user(Some("a_abc")).avatar_url(512, ImageFormat::Gif),
+            "https://cdn.discordapp.com/avatars/1/a_abc.gif?size=512",
+        );
+        assert_eq!(
+            user(Some("abc")).avatar_url(512, ImageFormat::Gif),
+            "https://cdn.discordapp.com/avatars/1/abc.png?size=512",
+        );
+    }
+
+    #[test]
+    fn test_avatar_url_size() {
+        let (user, png) = (user(Some("abc")), ImageFormat::Png);
+
+        assert!(user.avatar_url(100, png).ends_with("?size=128"));
+        assert!(user.avatar_url(1, png).ends_with("?size=16"));
+        assert!(user.avatar_url(9000, png).ends_with("?size=4096"));
+    }
+
+    #[test]
+    fn test_avatar_url_default() {
+        let expected = "https://cdn.discordapp.com/embed/avatars/2.png";
+
+        assert_eq!(user(None).avatar_url(128, ImageFormat::Png), expected);
+        assert_eq!(user(Some("")).avatar_url(128, ImageFormat::Png), expected);
+        assert_eq!(user(None).default_avatar_url(), expected);
+    }
+
+    #[test]
+    fn test_bot_urls() {
+        let plain = bot(None, Some(""));
+        assert_eq!(plain.page_url(), "https://discordbots.org/bot/1");
+        assert_eq!(plain.vanity_url(), None);
+        assert_eq!(plain.support_url(), None);
+        assert_eq!(
+            plain.avatar_url(64, ImageFormat::Png),
+            "https://cdn.discordapp.com/embed/avatars/2.png",
+        );
+
+        let vanity = bot(Some("test"), Some("abc"));
+        let page = "https://discordbots.org/bot/test";
+        assert_eq!(vanity.page_url(), page);
+        assert_eq!(vanity.vanity_url().as_deref(), Some(page));
+        assert_eq!(
+            vanity.support_url().as_deref(),
+            Some("https://discord.gg/abc"),
+        );
+    }
+
+    #[test]
+    fn test_banner_url() {
+        let mut user: User = serde_json::from_value(json!({
+            "admin": false,
+            "avatar": null,
+            "banner": "https://example.com/banner.png",
+            "bio": null,
+            "certifiedDev": false,
+            "color": null,
+            "defAvatar": null,
+            "discriminator": "0001",
+            "id": "2",
+            "mod": false,
+            "supporter": false,
+            "username": "test",
+            "webMod": false,
+        }))
+        .unwrap();
+        assert_eq!(user.banner_url(), Some("https://example.com/banner.png"));
+        assert_eq!(user.page_url(), "https://discordbots.org/user/2");
+
+        user.banner = Some(String::new());
+        assert_eq!(user.banner_url(), None);
+    }
+
+    #[test]
+    fn test_image_format() {
+        let user = user(Some("a_abc"));
+        let url = |format| user.avatar_url(16, format);
+
+        assert!(url(ImageFormat::Gif).ends_with("/a_abc.gif?size=16"));
+        assert!(url(ImageFormat::Jpeg).ends_with("/a_abc.jpg?size=16"));
+        assert!(url(ImageFormat::Png).ends_with("/a_abc.png?size=16"));
+        assert!(url(ImageFormat::WebP).ends_with("/a_abc.webp?size=16"));
+    }
+
+    #[test]
+    fn test_query_params() {
+        let mut webhook = Webhook {
+            bot: "1".to_owned(),
+            is_weekend: false,
+            kind: WebhookType::Upvote,
+            query: "?a=1&b=x%26y&a=2&c=hello+world&d".to_owned(),
+            user_id: "2".to_owned(),
+        };
+
+        assert_eq!(webhook.query_params(), vec![
+            ("a".to_owned(), "1".to_owned()),
+            ("b".to_owned(), "x&y".to_owned()),
+            ("a".to_owned(), "2".to_owned()),
+            ("c".to_owned(), "hello world".to_owned()),
+            ("d".to_owned(), String::new()),
+        ]);
+        assert_eq!(webhook.query_param("a").as_deref(), Some("1"));
+        assert_eq!(webhook.query_param("e"), None);
+
+        webhook.query.clear();
+        assert!(webhook.query_params().is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+//! Typed descriptions of the API's endpoints.
+//!
+//! A [`Route`] is the single source of truth for an endpoint's method, path,
+//! query params, whether it requires authorization and which rate limit it
+//! counts against.
+//!
+//! [`Route`]: enum.Route.html
+
+use chrono::Duration;
+use crate::{endpoints, Result};
+use url::{form_urlencoded, Url};
+
+/// The query params accepted by [`Route::GetBots`].
+///
+/// [`Route::GetBots`]: enum.Route.html#variant.GetBots
+const SEARCH_PARAMS: &[&str] = &["limit", "offset", "search", "sort"];
+
+/// The method of a request to a [`Route`].
+///
+/// [`Route`]: enum.Route.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Method {
+    /// A `GET` request.
+    Get,
+    /// A `POST` request.
+    Post,
+}
+
+impl Method {
+    /// The name of the method, such as `GET`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+        }
+    }
+}
+
+/// A rate limit which requests to [`Route`]s count against.
+///
+/// [`Route`]: enum.Route.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Bucket {
+    /// Requests to bot routes, limited to 60 per minute.
+    Bots,
+    /// All requests, limited to 100 per second.
+    Global,
+}
+
+impl Bucket {
+    /// The number of requests allowed in the bucket per window, along with
+    /// the length of the window.
+    pub fn limit(self) -> (usize, Duration) {
+        match self {
+            Bucket::Bots => (60, Duration::minutes(1)),
+            Bucket::Global => (100, Duration::seconds(1)),
+        }
+    }
+}
+
+/// An endpoint of the API, along with the params in its path and query.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Route {
+    /// Retrieves information about a bot.
+    GetBot {
+        /// The ID of the bot.
+        id: u64,
+    },
+    /// Retrieves a list of bots via a search.
+    GetBots {
+        /// The query params of the search.
+        params: Vec<(&'static str, String)>,
+    },
+    /// Retrieves information about a bot's specific stats.
+    GetBotStats {
+        /// The ID of the bot.
+        id: u64,
+    },
+    /// Retrieves whether a user has upvoted a bot in the last 24 hours.
+    GetBotVoteCheck {
+        /// The ID of the bot.
+        bot_id: u64,
+        /// The ID of the user.
+        user_id: u64,
+    },
+    /// Retrieves information to see who has upvoted a bot.
+    GetBotVotes {
+        /// The ID of the bot.
+        id: u64,
+    },
+    /// Retrieves information about a user.
+    GetUser {
+        /// The ID of the user.
+        id: u64,
+    },
+    /// Retrieves whether the weekend multiplier is in effect.
+    GetWeekend,
+    /// Posts a bot's shard stats.
+    PostBotStats {
+        /// The ID of the bot.
+        id: u64,
+    },
+}
+
+impl Route {
+    /// The rate limit which requests to the route count against.
+    pub fn bucket(&self) -> Bucket {
+        match self {
+            Route::GetUser { .. } | Route::GetWeekend => Bucket::Global,
+            _ => Bucket::Bots,
+        }
+    }
+
+    /// The method of requests to the route.
+    pub fn method(&self) -> Method {
+        match self {
+            Route::PostBotStats { .. } => Method::Post,
+            _ => Method::Get,
+        }
+    }
+
+    /// Parses a route from the method of a request and its path relative to
+    /// the API's base URL, including any query, such as `/bots/1/stats`.
+    ///
+    /// Returns `None` if the request isn't to a known route. Unknown query
+    /// params are ignored.
+    pub fn parse(method: Method, target: &str) -> Option<Self> {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = form_urlencoded::parse(query.as_bytes());
+        let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+        let id = |id: &str| id.parse::<u64>().ok();
+
+        Some(match (method, segments.as_slice()) {
+            (Method::Get, ["bots"]) => Route::GetBots {
+                params: query
+                    .filter_map(|(key, value)| {
+                        let key = SEARCH_PARAMS.iter().find(|k| **k == key)?;
+
+                        Some((*key, value.into_owned()))
+                    })
+                    .collect(),
+            },
+            (Method::Get, ["bots", bot]) => Route::GetBot { id: id(bot)? },
+            (Method::Get, ["bots", bot, "check"]) => Route::GetBotVoteCheck {
+                bot_id: id(bot)?,
+                user_id: query
+                    .into_iter()
+                    .find(|(key, _)| key == "userId")
+                    .and_then(|(_, value)| id(&value))?,
+            },
+            (Method::Get, ["bots", bot, "stats"]) => {
+                Route::GetBotStats { id: id(bot)? }
+            },
+            (Method::Get, ["bots", bot, "votes"]) => {
+                Route::GetBotVotes { id: id(bot)? }
+            },
+            (Method::Get, ["users", user]) => Route::GetUser { id: id(user)? },
+            (Method::Get, ["weekend"]) => Route::GetWeekend,
+            (Method::Post, ["bots", bot, "stats"]) => {
+                Route::PostBotStats { id: id(bot)? }
+            },
+            _ => return None,
+        })
+    }
+
+    /// The path of the route, relative to the API's base URL and without the
+    /// query, such as `/bots/1/stats`.
+    pub fn path(&self) -> String {
+        let template = self.template();
+
+        match self {
+            Route::GetBot { id }
+            | Route::GetBotStats { id }
+            | Route::GetBotVotes { id }
+            | Route::GetUser { id }
+            | Route::PostBotStats { id } => {
+                template.replace("{id}", &id.to_string())
+            },
+            Route::GetBotVoteCheck { bot_id, .. } => {
+                template.replace("{id}", &bot_id.to_string())
+            },
+            Route::GetBots { .. } | Route::GetWeekend => template.to_owned(),
+        }
+    }
+
+    /// The query params of the route.
+    pub fn query(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Route::GetBots { params } => params.clone(),
+            Route::GetBotVoteCheck { user_id, .. } => {
+                vec![("userId", user_id.to_string())]
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether requests to the route require an `Authorization` header.
+    pub fn requires_auth(&self) -> bool {
+        match self {
+            Route::GetBotVoteCheck { .. }
+            | Route::GetBotVotes { .. }
+            | Route::GetWeekend
+            | Route::PostBotStats { .. } => true,
+            Route::GetBot { .. }
+            | Route::GetBots { .. }
+            | Route::GetBotStats { .. }
+            | Route::GetUser { .. } => false,
+        }
+    }
+
+    /// The template of the route's path, such as `/bots/{id}/stats`.
+    ///
+    /// This is the same for all requests to the route, such as for grouping
+    /// them in logs.
+    pub fn template(&self) -> &'static str {
+        match self {
+            Route::GetBot { .. } => "/bots/{id}",
+            Route::GetBots { .. } => "/bots",
+            Route::GetBotStats { .. } | Route::PostBotStats { .. } => {
+                "/bots/{id}/stats"
+            },
+            Route::GetBotVoteCheck { .. } => "/bots/{id}/check",
+            Route::GetBotVotes { .. } => "/bots/{id}/votes",
+            Route::GetUser { .. } => "/users/{id}",
+            Route::GetWeekend => "/weekend",
+        }
+    }
+
+    /// Creates the full URL of the route, including its query.
+    pub fn url(&self) -> Result<Url> {
+        let url = format!("{}{}", endpoints::BASE, self.path());
+        let query = self.query();
+
+        if query.is_empty() {
+            Url::parse(&url).map_err(From::from)
+        } else {
+            Url::parse_with_params(&url, query).map_err(From::from)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bucket, Method, Route};
+
+    #[test]
+    fn test_url() {
+        let url = |route: Route| route.url().unwrap().to_string();
+
+        assert_eq!(
+            url(Route::GetBot { id: 1 }),
+            "https://discordbots.org/api/bots/1",
+        );
+        assert_eq!(
+            url(Route::GetBotStats { id: 1 }),
+            "https://discordbots.org/api/bots/1/stats",
+        );
+        assert_eq!(
+            url(Route::GetBotVotes { id: 1 }),
+            "https://discordbots.org/api/bots/1/votes",
+        );
+        assert_eq!(
+            url(Route::GetUser { id: 1 }),
+            "https://discordbots.org/api/users/1",
+        );
+        assert_eq!(
+            url(Route::GetBotVoteCheck { bot_id: 1, user_id: 2 }),
+            "https://discordbots.org/api/bots/1/check?userId=2",
+        );
+        assert_eq!(
+            url(Route::GetBots { params: vec![("limit", "5".to_owned())] }),
+            "https://discordbots.org/api/bots?limit=5",
+        );
+        assert_eq!(
+            url(Route::GetWeekend),
+            "https://discordbots.org/api/weekend",
+        );
+    }
+
+    #[test]
+    fn test_metadata() {
+        let route = Route::PostBotStats { id: 1 };
+        assert_eq!(route.method(), Method::Post);
+        assert_eq!(route.path(), "/bots/1/stats");
+        assert_eq!(route.template(), "/bots/{id}/stats");
+        assert_eq!(route.bucket(), Bucket::Bots);
+        assert!(route.requires_auth());
+
+        let route = Route::GetUser { id: 1 };
+        assert_eq!(route.method(), Method::Get);
+        assert_eq!(route.bucket(), Bucket::Global);
+        assert!(!route.requires_auth());
+    }
+
+    #[test]
+    fn test_parse() {
+        let routes = vec![
+            Route::GetBot { id: 1 },
+            Route::GetBots {
+                params: vec![("limit", "5".into()), ("search", "a b".into())],
+            },
+            Route::GetBotStats { id: 1 },
+            Route::GetBotVoteCheck { bot_id: 1, user_id: 2 },
+            Route::GetBotVotes { id: 1 },
+            Route::GetUser { id: 1 },
+            Route::GetWeekend,
+            Route::PostBotStats { id: 1 },
+        ];
+
+        for route in routes {
+            let url = route.url().unwrap();
+            let target = match url.query() {
+                Some(query) => format!("{}?{}", route.path(), query),
+                None => route.path(),
+            };
+
+            assert_eq!(Route::parse(route.method(), &target), Some(route));
+        }
+
+        assert_eq!(
+            Route::parse(Method::Get, "/bots?limit=5&foo=bar"),
+            Some(Route::GetBots { params: vec![("limit", "5".into())] }),
+        );
+        assert!(Route::parse(Method::Post, "/bots/1").is_none());
+        assert!(Route::parse(Method::Get, "/bots/a").is_none());
+        assert!(Route::parse(Method::Get, "/bots/1/check").is_none());
+    }
+}
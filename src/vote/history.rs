@@ -0,0 +1,383 @@
+use crate::{jsonl, Result};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+use super::Vote;
+
+/// How often a user can vote for a bot, in seconds.
+pub const VOTE_COOLDOWN_SECS: i64 = 12 * 60 * 60;
+
+/// A run of consecutive votes by a user.
+///
+/// Votes are consecutive if each was made within 12 hours of when the user
+/// could vote again, i.e. within 24 hours of the previous vote.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Streak {
+    /// When the last vote of the streak was made.
+    pub last_vote_at: DateTime<Utc>,
+    /// When the first vote of the streak was made.
+    pub started_at: DateTime<Utc>,
+    /// The number of votes in the streak.
+    pub votes: u32,
+}
+
+impl Streak {
+    /// The number of calendar days, in UTC, that the streak spans.
+    pub fn days(&self) -> i64 {
+        let start = self.started_at.date_naive();
+        let end = self.last_vote_at.date_naive();
+
+        (end - start).num_days() + 1
+    }
+}
+
+/// A history of votes for a bot, keyed by user ID.
+///
+/// A history is either only kept in memory, or [opened] from a JSON-lines
+/// file which each recorded vote is appended to, so that users' streaks
+/// survive restarts.
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::{Duration, TimeZone, Utc};
+/// use discord_bots_org::vote::{History, Vote};
+///
+/// let mut history = History::new("270198738570444801");
+/// let start = Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap();
+///
+/// for day in 0..14 {
+///     history.record(&Vote {
+///         at: start + Duration::days(day),
+///         bot: "270198738570444801".to_owned(),
+///         is_test: false,
+///         is_weekend: false,
+///         user_id: "114941315417899012".to_owned(),
+///     })?;
+/// }
+///
+/// let now = start + Duration::days(13) + Duration::hours(1);
+/// let streak = history.current_streak("114941315417899012", now).unwrap();
+/// println!("You have voted {} days in a row", streak.days());
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// [opened]: #method.open
+#[derive(Debug)]
+pub struct History {
+    bot: String,
+    /// The file votes are appended to, if any.
+    file: Option<BufWriter<File>>,
+    /// The votes by each user, ordered by when they were made.
+    votes: HashMap<String, Vec<Vote>>,
+}
+
+impl History {
+    /// Creates a new, empty history for the bot with the given ID, which is
+    /// only kept in memory.
+    pub fn new(bot: impl Into<String>) -> Self {
+        Self {
+            bot: bot.into(),
+            file: None,
+            votes: HashMap::new(),
+        }
+    }
+
+    /// Opens the history for the bot with the given ID stored at the given
+    /// path, creating it if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file could not be read or created, or
+    /// [`Error::Json`] if it contains an invalid line. A final line which was
+    /// only partly written, such as because the process crashed, is removed
+    /// instead.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [`Error::Json`]: ../enum.Error.html#variant.Json
+    pub fn open(
+        bot: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let (votes, file) = jsonl::open::<Vote>(path.as_ref())?;
+        let mut history = Self::new(bot);
+
+        for vote in votes {
+            if let Some(index) = history.index(&vote) {
+                history.insert(index, vote);
+            }
+        }
+
+        history.file = Some(BufWriter::new(file));
+
+        Ok(history)
+    }
+
+    /// The ID of the bot that the history is for.
+    pub fn bot(&self) -> &str {
+        &self.bot
+    }
+
+    /// Records a vote, returning whether it was recorded.
+    ///
+    /// Test votes, votes for other bots and votes made within 12 hours of
+    /// an already recorded vote by the same user aren't recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the vote could not be written to the
+    /// history's file.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn record(&mut self, vote: &Vote) -> Result<bool> {
+        let index = match self.index(vote) {
+            Some(index) => index,
+            None => return Ok(false),
+        };
+
+        if let Some(writer) = self.file.as_mut() {
+            serde_json::to_writer(&mut *writer, vote)?;
+            writer.write_all(b"\n")?;
+            writer.flush()?;
+            writer.get_ref().sync_data()?;
+        }
+
+        self.insert(index, vote.clone());
+
+        Ok(true)
+    }
+
+    /// The votes by a user, ordered by when they were made.
+    pub fn votes(&self, user_id: &str) -> &[Vote] {
+        self.votes.get(user_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// When a user can next vote, which is `now` if they can vote already.
+    pub fn next_vote_at(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        let cooldown = Duration::seconds(VOTE_COOLDOWN_SECS);
+
+        match self.votes(user_id).last() {
+            Some(last) => now.max(last.at + cooldown),
+            None => now,
+        }
+    }
+
+    /// All of a user's streaks, ordered by when they were made.
+    pub fn streaks(&self, user_id: &str) -> Vec<Streak> {
+        let window = Duration::seconds(VOTE_COOLDOWN_SECS * 2);
+        let mut streaks = Vec::<Streak>::new();
+
+        for vote in self.votes(user_id) {
+            match streaks.last_mut() {
+                Some(streak) if vote.at - streak.last_vote_at <= window => {
+                    streak.last_vote_at = vote.at;
+                    streak.votes += 1;
+                },
+                _ => streaks.push(Streak {
+                    last_vote_at: vote.at,
+                    started_at: vote.at,
+                    votes: 1,
+                }),
+            }
+        }
+
+        streaks
+    }
+
+    /// A user's current streak, if they can still continue it.
+    pub fn current_streak(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> Option<Streak> {
+        let window = Duration::seconds(VOTE_COOLDOWN_SECS * 2);
+
+        self.streaks(user_id)
+            .pop()
+            .filter(|streak| now - streak.last_vote_at <= window)
+    }
+
+    /// A user's longest streak, preferring the earliest if there are
+    /// several.
+    pub fn longest_streak(&self, user_id: &str) -> Option<Streak> {
+        self.streaks(user_id)
+            .into_iter()
+            .rev()
+            .max_by_key(|streak| streak.votes)
+    }
+
+    /// Exports all votes as CSV with the columns `user_id`, `voted_at` and
+    /// `is_weekend`, ordered by user and then by when they were made.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the writer could not be written to.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn export(&self, mut writer: impl Write) -> Result<()> {
+        let mut users = self.votes.keys().collect::<Vec<_>>();
+        users.sort();
+
+        writeln!(writer, "user_id,voted_at,is_weekend")?;
+
+        for user_id in users {
+            for vote in self.votes(user_id) {
+                writeln!(
+                    writer,
+                    "{},{},{}",
+                    user_id,
+                    vote.at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    vote.is_weekend,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The index to insert a vote into its user's votes at, or `None` if it
+    /// shouldn't be recorded.
+    fn index(&self, vote: &Vote) -> Option<usize> {
+        if vote.is_test || vote.bot != self.bot {
+            return None;
+        }
+
+        let votes = self.votes(&vote.user_id);
+        let index = votes.partition_point(|other| other.at <= vote.at);
+        let cooldown = Duration::seconds(VOTE_COOLDOWN_SECS);
+
+        let too_close = |other: &Vote| (vote.at - other.at).abs() < cooldown;
+
+        if index.checked_sub(1).is_some_and(|i| too_close(&votes[i]))
+            || votes.get(index).is_some_and(too_close)
+        {
+            return None;
+        }
+
+        Some(index)
+    }
+
+    fn insert(&mut self, index: usize, vote: Vote) {
+        self.votes
+            .entry(vote.user_id.clone())
+            .or_default()
+            .insert(index, vote);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{vote::Vote, Result};
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use std::{env, fs, process};
+    use super::History;
+
+    fn vote(user_id: &str, at: DateTime<Utc>) -> Vote {
+        Vote {
+            at,
+            bot: "1".to_owned(),
+            is_test: false,
+            is_weekend: false,
+            user_id: user_id.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_streaks() -> Result<()> {
+        let mut history = History::new("1");
+        let start = Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap();
+
+        for hours in &[0, 12, 30, 48, 100, 120, 140] {
+            let at = start + Duration::hours(*hours);
+            assert!(history.record(&vote("2", at))?);
+        }
+
+        assert!(!history.record(&vote("2", start + Duration::hours(6)))?);
+        assert!(!history.record(&Vote {
+            bot: "3".to_owned(),
+            ..vote("2", start + Duration::days(30))
+        })?);
+
+        let streaks = history.streaks("2");
+        assert_eq!(streaks.len(), 2);
+        assert_eq!(streaks[0].votes, 4);
+        assert_eq!(streaks[0].days(), 3);
+        assert_eq!(history.longest_streak("2"), Some(streaks[0]));
+
+        let now = start + Duration::hours(150);
+        assert_eq!(history.current_streak("2", now), Some(streaks[1]));
+        assert_eq!(
+            history.current_streak("2", start + Duration::hours(165)),
+            None,
+        );
+        assert_eq!(
+            history.next_vote_at("2", now),
+            start + Duration::hours(152),
+        );
+        assert_eq!(history.next_vote_at("3", now), now);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open() -> Result<()> {
+        let path = env::temp_dir()
+            .join(format!("dbl-history-{}.jsonl", process::id()));
+        let start = Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap();
+        let _ = fs::remove_file(&path);
+
+        let streaks = {
+            let mut history = History::open("1", &path)?;
+
+            for hours in &[0, 20, 40, 100, 120] {
+                history.record(&vote("2", start + Duration::hours(*hours)))?;
+            }
+
+            assert!(!history.record(&vote("2", start))?);
+            history.record(&vote("3", start))?;
+
+            history.streaks("2")
+        };
+
+        // Streaks survive reopening the history.
+        let mut history = History::open("1", &path)?;
+        assert_eq!(history.streaks("2"), streaks);
+        assert_eq!(history.votes("3").len(), 1);
+
+        let now = start + Duration::hours(140);
+        assert!(history.record(&vote("2", now))?);
+        let streak = History::open("1", &path)?.current_streak("2", now);
+        assert_eq!(streak.map(|streak| streak.votes), Some(3));
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export() -> Result<()> {
+        let mut history = History::new("1");
+        let start = Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap();
+        history.record(&vote("3", start))?;
+        history.record(&vote("2", start + Duration::hours(1)))?;
+
+        let mut csv = Vec::new();
+        history.export(&mut csv)?;
+
+        assert_eq!(String::from_utf8_lossy(&csv), concat!(
+            "user_id,voted_at,is_weekend\n",
+            "2,2019-01-01T01:00:00Z,false\n",
+            "3,2019-01-01T00:00:00Z,false\n",
+        ));
+
+        Ok(())
+    }
+}
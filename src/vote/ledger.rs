@@ -0,0 +1,173 @@
+use crate::{jsonl, Result};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+use super::rules::Grant;
+
+/// A store of granted rewards.
+pub trait Ledger {
+    /// Records a granted reward.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grant could not be stored.
+    fn record(&mut self, grant: &Grant) -> Result<()>;
+
+    /// The rewards granted to a user for votes for a bot, in the order they
+    /// were recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grants could not be retrieved.
+    fn grants(&self, bot: &str, user_id: &str) -> Result<Vec<Grant>>;
+
+    /// The total amount of rewards granted to a user for votes for any bot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the balance could not be retrieved.
+    fn balance(&self, user_id: &str) -> Result<u64>;
+}
+
+/// A ledger which is only kept in memory.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryLedger {
+    grants: Vec<Grant>,
+}
+
+impl MemoryLedger {
+    /// Creates a new, empty ledger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Ledger for MemoryLedger {
+    fn record(&mut self, grant: &Grant) -> Result<()> {
+        self.grants.push(grant.clone());
+
+        Ok(())
+    }
+
+    fn grants(&self, bot: &str, user_id: &str) -> Result<Vec<Grant>> {
+        Ok(self
+            .grants
+            .iter()
+            .filter(|grant| grant.bot == bot && grant.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    fn balance(&self, user_id: &str) -> Result<u64> {
+        Ok(self
+            .grants
+            .iter()
+            .filter(|grant| grant.user_id == user_id)
+            .map(|grant| grant.amount)
+            .sum())
+    }
+}
+
+/// A ledger stored in an append-only JSON-lines file.
+///
+/// The file is read into memory when opened, and each grant is flushed to
+/// disk before being recorded.
+#[derive(Debug)]
+pub struct FileLedger {
+    file: BufWriter<File>,
+    memory: MemoryLedger,
+}
+
+impl FileLedger {
+    /// Opens the ledger stored at the given path, creating it if it doesn't
+    /// exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file could not be read or created, or
+    /// [`Error::Json`] if it contains an invalid line. A final line which was
+    /// only partly written, such as because the process crashed, is removed
+    /// instead.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [`Error::Json`]: ../enum.Error.html#variant.Json
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let (grants, file) = jsonl::open(path.as_ref())?;
+
+        Ok(Self {
+            file: BufWriter::new(file),
+            memory: MemoryLedger {
+                grants,
+            },
+        })
+    }
+}
+
+impl Ledger for FileLedger {
+    fn record(&mut self, grant: &Grant) -> Result<()> {
+        serde_json::to_writer(&mut self.file, grant)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()?;
+        self.file.get_ref().sync_data()?;
+
+        self.memory.record(grant)
+    }
+
+    fn grants(&self, bot: &str, user_id: &str) -> Result<Vec<Grant>> {
+        self.memory.grants(bot, user_id)
+    }
+
+    fn balance(&self, user_id: &str) -> Result<u64> {
+        self.memory.balance(user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use chrono::{TimeZone, Utc};
+    use std::{
+        env,
+        fs::{self, OpenOptions},
+        io::Write,
+        process,
+    };
+    use super::{
+        super::rules::Grant,
+        FileLedger,
+        Ledger,
+    };
+
+    #[test]
+    fn test_torn_line() -> Result<()> {
+        let path = env::temp_dir()
+            .join(format!("dbl-ledger-{}.jsonl", process::id()));
+        let grant = |user_id: &str| Grant {
+            amount: 10,
+            at: Utc.timestamp_opt(1_546_300_800, 0).unwrap(),
+            bot: "1".to_owned(),
+            streak: 1,
+            user_id: user_id.to_owned(),
+            weekend: false,
+        };
+
+        FileLedger::open(&path)?.record(&grant("2"))?;
+
+        // A crash while recording leaves half a grant.
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        file.write_all(br#"{"amount":10,"at":"2019-01-01T00:00:00Z","bo"#)?;
+        drop(file);
+
+        FileLedger::open(&path)?.record(&grant("3"))?;
+
+        let ledger = FileLedger::open(&path)?;
+        assert_eq!(ledger.balance("2")?, 10);
+        assert_eq!(ledger.grants("1", "3")?.len(), 1);
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+}
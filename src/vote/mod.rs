@@ -0,0 +1,123 @@
+//! Utilities for rewarding users for their votes, tracking their voting
+//! streaks and reminding them to vote.
+//!
+//! Votes can come from any source, such as [`Webhook`]s received by a
+//! [`Receiver`], or by polling a bot's votes. Each [`Vote`] is evaluated
+//! against a set of reward [`Rules`] by a [`Rewarder`], which records
+//! granted rewards in a [`Ledger`]. Votes can also be recorded in a
+//! [`History`] to track users' voting streaks, or used by a [`Scheduler`] to
+//! remind users when they can vote again.
+//!
+//! # Examples
+//!
+//! Give 10 credits per vote, doubled on weekends, with a bonus of 1 credit
+//! per consecutive vote up to 5, and at most 100 credits per week:
+//!
+//! ```rust,no_run
+//! use chrono::Duration;
+//! use discord_bots_org::{
+//!     model::Webhook,
+//!     vote::{FileLedger, Reward, Rewarder, Rules, Vote},
+//! };
+//!
+//! let webhook = serde_json::from_str::<Webhook>(r#"{
+//!     "bot": "270198738570444801",
+//!     "user": "114941315417899012",
+//!     "type": "upvote",
+//!     "isWeekend": false,
+//!     "query": ""
+//! }"#)?;
+//!
+//! let mut rules = Rules::new();
+//! rules
+//!     .base(10)
+//!     .weekend_multiplier(2)
+//!     .streak_bonus(1, 5)
+//!     .cap(100, Duration::weeks(1));
+//!
+//! let mut rewarder = Rewarder::new(rules, FileLedger::open("rewards.jsonl")?);
+//!
+//! match rewarder.process(&Vote::from(&webhook))? {
+//!     Reward::Granted(grant) => println!("Gave {} credits", grant.amount),
+//!     Reward::Skipped(why) => println!("Gave nothing: {:?}", why),
+//! }
+//! # Ok::<(), discord_bots_org::Error>(())
+//! ```
+//!
+//! [`History`]: struct.History.html
+//! [`Ledger`]: trait.Ledger.html
+//! [`Receiver`]: ../webhook/struct.Receiver.html
+//! [`Rewarder`]: struct.Rewarder.html
+//! [`Rules`]: struct.Rules.html
+//! [`Scheduler`]: struct.Scheduler.html
+//! [`Vote`]: struct.Vote.html
+//! [`Webhook`]: ../model/struct.Webhook.html
+
+mod clock;
+mod history;
+mod ledger;
+mod rules;
+mod scheduler;
+mod weekend;
+
+pub use self::{
+    clock::{Clock, ManualClock, SystemClock},
+    history::{History, Streak, VOTE_COOLDOWN_SECS},
+    ledger::{FileLedger, Ledger, MemoryLedger},
+    rules::{Grant, Reward, Rewarder, Rules, Skip},
+    scheduler::{Reminder, Scheduler},
+    weekend::{is_weekend, next_weekend},
+};
+
+use crate::{
+    model::{Webhook, WebhookType},
+    webhook::Event,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A vote for a bot by a user.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vote {
+    /// When the vote was made.
+    pub at: DateTime<Utc>,
+    /// The ID of the bot that was voted for.
+    pub bot: String,
+    /// Whether the vote is a test vote.
+    pub is_test: bool,
+    /// Whether the weekend multiplier was in effect.
+    pub is_weekend: bool,
+    /// The ID of the user who voted.
+    pub user_id: String,
+}
+
+impl Vote {
+    /// Creates a vote from a webhook received at the given time.
+    pub fn from_webhook(webhook: &Webhook, at: DateTime<Utc>) -> Self {
+        Self {
+            at,
+            bot: webhook.bot.clone(),
+            is_test: match webhook.kind {
+                WebhookType::Test => true,
+                WebhookType::Upvote => false,
+            },
+            is_weekend: webhook.is_weekend,
+            user_id: webhook.user_id.clone(),
+        }
+    }
+}
+
+/// Creates a vote from a webhook received now.
+impl From<&Webhook> for Vote {
+    fn from(webhook: &Webhook) -> Self {
+        Self::from_webhook(webhook, Utc::now())
+    }
+}
+
+/// Creates a vote from a webhook at the time it was received.
+impl From<&Event> for Vote {
+    fn from(event: &Event) -> Self {
+        Self::from_webhook(&event.webhook, event.received_at)
+    }
+}
@@ -0,0 +1,395 @@
+use crate::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use super::{history::VOTE_COOLDOWN_SECS, ledger::Ledger, Vote};
+
+/// A reward granted for a vote.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Grant {
+    /// The total amount granted.
+    ///
+    /// This is 0 for a vote which was [capped], which is recorded so that
+    /// it still continues the user's streak.
+    ///
+    /// [capped]: enum.Skip.html#variant.Capped
+    pub amount: u64,
+    /// When the vote was made.
+    pub at: DateTime<Utc>,
+    /// The ID of the bot that was voted for.
+    pub bot: String,
+    /// The length of the user's voting streak, including this vote.
+    pub streak: u32,
+    /// The ID of the user who voted.
+    pub user_id: String,
+    /// Whether the weekend multiplier was applied.
+    pub weekend: bool,
+}
+
+/// The reason a vote was not rewarded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Skip {
+    /// The user has already been rewarded up to the cap.
+    ///
+    /// The vote still counts towards the user's streak and cooldown, so a
+    /// [`Rewarder`] records it as a grant of 0.
+    ///
+    /// [`Rewarder`]: struct.Rewarder.html
+    Capped {
+        /// The length of the user's voting streak, including this vote.
+        streak: u32,
+    },
+    /// The user was rewarded too recently.
+    Cooldown {
+        /// When the user can next be rewarded.
+        until: DateTime<Utc>,
+    },
+    /// The vote is a test vote, and test votes are not rewarded.
+    Test,
+}
+
+/// The result of evaluating a vote against [`Rules`].
+///
+/// [`Rules`]: struct.Rules.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Reward {
+    /// The vote was rewarded.
+    Granted(Grant),
+    /// The vote was not rewarded.
+    Skipped(Skip),
+}
+
+/// A declarative set of rules for rewarding votes.
+///
+/// By default, each vote is rewarded with 1, doubled on weekends. Votes less
+/// than 12 hours after the last rewarded vote and test votes aren't
+/// rewarded.
+#[derive(Clone, Debug)]
+pub struct Rules {
+    allow_test: bool,
+    base: u64,
+    cap: Option<(u64, Duration)>,
+    cooldown: Duration,
+    streak_bonus: u64,
+    streak_bonus_max: u64,
+    streak_window: Duration,
+    weekend_multiplier: u64,
+}
+
+impl Rules {
+    /// Creates a new set of rules with the default values.
+    pub fn new() -> Self {
+        Self {
+            allow_test: false,
+            base: 1,
+            cap: None,
+            cooldown: Duration::seconds(VOTE_COOLDOWN_SECS),
+            streak_bonus: 0,
+            streak_bonus_max: 0,
+            streak_window: Duration::seconds(VOTE_COOLDOWN_SECS * 2),
+            weekend_multiplier: 2,
+        }
+    }
+
+    /// Sets whether test votes are rewarded.
+    ///
+    /// Defaults to false.
+    pub fn allow_test(&mut self, allow_test: bool) -> &mut Self {
+        self.allow_test = allow_test;
+
+        self
+    }
+
+    /// Sets the base reward of a vote.
+    ///
+    /// Defaults to 1.
+    pub fn base(&mut self, base: u64) -> &mut Self {
+        self.base = base;
+
+        self
+    }
+
+    /// Sets the maximum amount a user can be rewarded within a period, such
+    /// as 100 per week.
+    ///
+    /// A reward which would exceed the cap is reduced to the remaining
+    /// amount.
+    pub fn cap(&mut self, amount: u64, period: Duration) -> &mut Self {
+        self.cap = Some((amount, period));
+
+        self
+    }
+
+    /// Sets the minimum time between rewarded votes.
+    ///
+    /// Defaults to 12 hours, which is how often a user can vote.
+    pub fn cooldown(&mut self, cooldown: Duration) -> &mut Self {
+        self.cooldown = cooldown;
+
+        self
+    }
+
+    /// Sets the bonus added for each consecutive vote after the first in a
+    /// streak, and the maximum total bonus.
+    ///
+    /// Defaults to no bonus.
+    pub fn streak_bonus(&mut self, per_vote: u64, max: u64) -> &mut Self {
+        self.streak_bonus = per_vote;
+        self.streak_bonus_max = max;
+
+        self
+    }
+
+    /// Sets the maximum time between votes for them to count as
+    /// consecutive.
+    ///
+    /// Defaults to 24 hours.
+    pub fn streak_window(&mut self, window: Duration) -> &mut Self {
+        self.streak_window = window;
+
+        self
+    }
+
+    /// Sets the multiplier applied to rewards for votes made while the
+    /// weekend multiplier is in effect.
+    ///
+    /// Defaults to 2.
+    pub fn weekend_multiplier(&mut self, multiplier: u64) -> &mut Self {
+        self.weekend_multiplier = multiplier;
+
+        self
+    }
+
+    /// Evaluates a vote given the rewards previously granted to the user for
+    /// the bot, in the order they were granted.
+    ///
+    /// Grants of 0 for capped votes count towards the cooldown and streak,
+    /// but not the cap.
+    pub fn evaluate(&self, vote: &Vote, grants: &[Grant]) -> Reward {
+        if vote.is_test && !self.allow_test {
+            return Reward::Skipped(Skip::Test);
+        }
+
+        if let Some(last) = grants.last() {
+            let until = last.at + self.cooldown;
+
+            if vote.at < until {
+                return Reward::Skipped(Skip::Cooldown {
+                    until,
+                });
+            }
+        }
+
+        let streak = match grants.last() {
+            Some(last) if vote.at - last.at <= self.streak_window => {
+                last.streak.saturating_add(1)
+            },
+            _ => 1,
+        };
+
+        let bonus = self
+            .streak_bonus
+            .saturating_mul(u64::from(streak - 1))
+            .min(self.streak_bonus_max);
+        let mut amount = self.base.saturating_add(bonus);
+
+        if vote.is_weekend {
+            amount = amount.saturating_mul(self.weekend_multiplier);
+        }
+
+        if let Some((cap, period)) = self.cap {
+            let since = vote.at - period;
+            let granted = grants
+                .iter()
+                .filter(|grant| grant.at >= since)
+                .map(|grant| grant.amount)
+                .sum::<u64>();
+
+            amount = amount.min(cap.saturating_sub(granted));
+
+            if amount == 0 {
+                return Reward::Skipped(Skip::Capped {
+                    streak,
+                });
+            }
+        }
+
+        Reward::Granted(Grant {
+            amount,
+            at: vote.at,
+            bot: vote.bot.clone(),
+            streak,
+            user_id: vote.user_id.clone(),
+            weekend: vote.is_weekend,
+        })
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Evaluates votes against [`Rules`], recording granted rewards in a
+/// [`Ledger`].
+///
+/// [`Ledger`]: trait.Ledger.html
+/// [`Rules`]: struct.Rules.html
+#[derive(Debug)]
+pub struct Rewarder<L> {
+    ledger: L,
+    rules: Rules,
+}
+
+impl<L: Ledger> Rewarder<L> {
+    /// Creates a new rewarder with the given rules and ledger.
+    pub fn new(rules: Rules, ledger: L) -> Self {
+        Self {
+            ledger,
+            rules,
+        }
+    }
+
+    /// The ledger that rewards are recorded in.
+    pub fn ledger(&self) -> &L {
+        &self.ledger
+    }
+
+    /// The rules that votes are evaluated against.
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Evaluates a vote, recording the reward in the ledger if one is
+    /// granted.
+    ///
+    /// Capped votes are recorded as a grant of 0, so that they continue the
+    /// user's streak.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ledger could not be read from or written to.
+    pub fn process(&mut self, vote: &Vote) -> Result<Reward> {
+        let grants = self.ledger.grants(&vote.bot, &vote.user_id)?;
+        let reward = self.rules.evaluate(vote, &grants);
+
+        match &reward {
+            Reward::Granted(grant) => self.ledger.record(grant)?,
+            Reward::Skipped(Skip::Capped { streak }) => {
+                self.ledger.record(&Grant {
+                    amount: 0,
+                    at: vote.at,
+                    bot: vote.bot.clone(),
+                    streak: *streak,
+                    user_id: vote.user_id.clone(),
+                    weekend: vote.is_weekend,
+                })?;
+            },
+            Reward::Skipped(_) => {},
+        }
+
+        Ok(reward)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        vote::{Ledger, MemoryLedger, Vote},
+        Result,
+    };
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use super::{Reward, Rewarder, Rules, Skip};
+
+    fn vote(at: DateTime<Utc>, is_weekend: bool) -> Vote {
+        Vote {
+            at,
+            bot: "1".to_owned(),
+            is_test: false,
+            is_weekend,
+            user_id: "2".to_owned(),
+        }
+    }
+
+    fn amount(reward: Reward) -> u64 {
+        match reward {
+            Reward::Granted(grant) => grant.amount,
+            Reward::Skipped(why) => panic!("skipped: {:?}", why),
+        }
+    }
+
+    #[test]
+    fn test_rules() -> Result<()> {
+        let mut rules = Rules::new();
+        rules
+            .base(10)
+            .weekend_multiplier(3)
+            .streak_bonus(2, 3)
+            .cap(48, Duration::days(2));
+        let mut rewarder = Rewarder::new(rules, MemoryLedger::new());
+        let at = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+
+        let mut test = vote(at, false);
+        test.is_test = true;
+        assert_eq!(rewarder.process(&test)?, Reward::Skipped(Skip::Test));
+
+        assert_eq!(amount(rewarder.process(&vote(at, false))?), 10);
+        assert_eq!(
+            rewarder.process(&vote(at + Duration::hours(6), false))?,
+            Reward::Skipped(Skip::Cooldown {
+                until: at + Duration::hours(12),
+            }),
+        );
+
+        let at = at + Duration::hours(12);
+        assert_eq!(amount(rewarder.process(&vote(at, false))?), 12);
+
+        let at = at + Duration::hours(12);
+        assert_eq!(amount(rewarder.process(&vote(at, false))?), 13);
+
+        let at = at + Duration::hours(12);
+        assert_eq!(amount(rewarder.process(&vote(at, false))?), 13);
+
+        let at = at + Duration::hours(12);
+        assert_eq!(
+            rewarder.process(&vote(at, false))?,
+            Reward::Skipped(Skip::Capped {
+                streak: 5,
+            }),
+        );
+
+        let at = at + Duration::days(3);
+        assert_eq!(amount(rewarder.process(&vote(at, true))?), 30);
+        assert_eq!(rewarder.ledger().balance("2")?, 78);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_capped_streak() -> Result<()> {
+        let mut rules = Rules::new();
+        rules.base(10).cap(20, Duration::hours(36));
+        let mut rewarder = Rewarder::new(rules, MemoryLedger::new());
+        let start = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+        let mut streaks = Vec::new();
+
+        for hours in (0..=48).step_by(12) {
+            let vote = vote(start + Duration::hours(hours), false);
+
+            streaks.push(match rewarder.process(&vote)? {
+                Reward::Granted(grant) => grant.streak,
+                Reward::Skipped(Skip::Capped { streak }) => streak,
+                Reward::Skipped(why) => panic!("skipped: {:?}", why),
+            });
+        }
+
+        // Votes while capped continue the streak, even though the last
+        // rewarded vote is more than a streak window before the next.
+        assert_eq!(streaks, [1, 2, 3, 4, 5]);
+        assert_eq!(rewarder.ledger().grants("1", "2")?.len(), 5);
+        assert_eq!(rewarder.ledger().balance("2")?, 30);
+
+        Ok(())
+    }
+}
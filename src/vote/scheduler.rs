@@ -0,0 +1,351 @@
+use crate::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use super::{clock::Clock, history::VOTE_COOLDOWN_SECS, Vote};
+
+/// A reminder for a user that they can vote for a bot again.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reminder {
+    /// The ID of the bot that the user can vote for.
+    pub bot: String,
+    /// When the user can vote again.
+    pub due_at: DateTime<Utc>,
+    /// The ID of the user to remind.
+    pub user_id: String,
+}
+
+/// The persisted state of a scheduler.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    opted_out: BTreeSet<String>,
+    reminders: Vec<Reminder>,
+}
+
+/// Schedules reminders for users for when they can vote again.
+///
+/// Reminders are scheduled from votes, or from the results of vote checks,
+/// and the callback is called with each reminder once it is due when
+/// [`run_pending`] is called. A user only has one reminder per bot, and
+/// users who have opted out are never reminded.
+///
+/// When opened from a file, the scheduler's reminders and opted out users
+/// are saved to it after each change, so reminders which became due while
+/// the process was stopped are fired when it is next run.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use discord_bots_org::vote::{Scheduler, SystemClock};
+/// use std::{thread, time::Duration};
+///
+/// let mut scheduler = Scheduler::open(
+///     "reminders.json",
+///     SystemClock,
+///     |reminder| println!("<@{}>, you can vote again!", reminder.user_id),
+/// )?;
+///
+/// // Schedule reminders from votes, then:
+/// loop {
+///     scheduler.run_pending()?;
+///     thread::sleep(Duration::from_secs(1));
+/// }
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// [`run_pending`]: #method.run_pending
+pub struct Scheduler<C, F> {
+    callback: F,
+    clock: C,
+    path: Option<PathBuf>,
+    state: State,
+}
+
+impl<C: Clock, F: FnMut(&Reminder)> Scheduler<C, F> {
+    /// Opens a scheduler saved at the given path, creating it when first
+    /// saved if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file could not be read, or
+    /// [`Error::Json`] if it is invalid.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [`Error::Json`]: ../enum.Error.html#variant.Json
+    pub fn open(
+        path: impl AsRef<Path>,
+        clock: C,
+        callback: F,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let state = if path.exists() {
+            serde_json::from_reader(File::open(path)?)?
+        } else {
+            State::default()
+        };
+
+        Ok(Self {
+            callback,
+            clock,
+            path: Some(path.to_owned()),
+            state,
+        })
+    }
+
+    /// Creates a scheduler which is only kept in memory.
+    pub fn memory(clock: C, callback: F) -> Self {
+        Self {
+            callback,
+            clock,
+            path: None,
+            state: State::default(),
+        }
+    }
+
+    /// Schedules a reminder for when the user can next vote after a vote.
+    ///
+    /// Test votes are ignored. Returns the scheduled reminder, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the scheduler could not be saved.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn schedule_vote(&mut self, vote: &Vote) -> Result<Option<Reminder>> {
+        if vote.is_test {
+            return Ok(None);
+        }
+
+        let due_at = vote.at + Duration::seconds(VOTE_COOLDOWN_SECS);
+
+        self.schedule(&vote.bot, &vote.user_id, due_at)
+    }
+
+    /// Schedules a reminder from the result of checking whether a user has
+    /// voted for a bot in the last 12 hours.
+    ///
+    /// As the time of the vote isn't known, the reminder is due 12 hours
+    /// from now, unless an earlier reminder is already scheduled. Nothing is
+    /// scheduled if the user hasn't voted, as they can vote already.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the scheduler could not be saved.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn schedule_check(
+        &mut self,
+        bot: &str,
+        user_id: &str,
+        voted: bool,
+    ) -> Result<Option<Reminder>> {
+        if !voted {
+            return Ok(None);
+        }
+
+        if let Some(existing) = self.reminder(bot, user_id) {
+            return Ok(Some(existing.clone()));
+        }
+
+        let due_at = self.clock.now() + Duration::seconds(VOTE_COOLDOWN_SECS);
+
+        self.schedule(bot, user_id, due_at)
+    }
+
+    /// Opts a user out of reminders, cancelling any scheduled for them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the scheduler could not be saved.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn opt_out(&mut self, user_id: &str) -> Result<()> {
+        self.state.reminders.retain(|r| r.user_id != user_id);
+        self.state.opted_out.insert(user_id.to_owned());
+
+        self.save()
+    }
+
+    /// Opts a user back in to reminders.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the scheduler could not be saved.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn opt_in(&mut self, user_id: &str) -> Result<()> {
+        if self.state.opted_out.remove(user_id) {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether a user has opted out of reminders.
+    pub fn is_opted_out(&self, user_id: &str) -> bool {
+        self.state.opted_out.contains(user_id)
+    }
+
+    /// The scheduled reminder for a user for a bot, if any.
+    pub fn reminder(&self, bot: &str, user_id: &str) -> Option<&Reminder> {
+        self.state
+            .reminders
+            .iter()
+            .find(|r| r.bot == bot && r.user_id == user_id)
+    }
+
+    /// When the next reminder is due, if any are scheduled.
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.state.reminders.iter().map(|r| r.due_at).min()
+    }
+
+    /// Calls the callback with each reminder which is due, in the order they
+    /// became due, returning how many were fired.
+    ///
+    /// Fired reminders are only removed from the saved scheduler after all
+    /// of them have been fired, so a reminder may be fired again if the
+    /// process stops while firing them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the scheduler could not be saved.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn run_pending(&mut self) -> Result<usize> {
+        let now = self.clock.now();
+        let (mut due, pending) = self
+            .state
+            .reminders
+            .drain(..)
+            .partition::<Vec<_>, _>(|r| r.due_at <= now);
+        self.state.reminders = pending;
+
+        if due.is_empty() {
+            return Ok(0);
+        }
+
+        due.sort_by_key(|r| r.due_at);
+
+        for reminder in &due {
+            (self.callback)(reminder);
+        }
+
+        self.save()?;
+
+        Ok(due.len())
+    }
+
+    fn schedule(
+        &mut self,
+        bot: &str,
+        user_id: &str,
+        due_at: DateTime<Utc>,
+    ) -> Result<Option<Reminder>> {
+        if self.is_opted_out(user_id) {
+            return Ok(None);
+        }
+
+        let reminder = Reminder {
+            bot: bot.to_owned(),
+            due_at,
+            user_id: user_id.to_owned(),
+        };
+
+        self.state
+            .reminders
+            .retain(|r| r.bot != bot || r.user_id != user_id);
+        self.state.reminders.push(reminder.clone());
+        self.save()?;
+
+        Ok(Some(reminder))
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, &self.state)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        vote::{ManualClock, Vote},
+        Result,
+    };
+    use chrono::{Duration, TimeZone, Utc};
+    use std::{cell::RefCell, env, fs, process, rc::Rc};
+    use super::{Reminder, Scheduler};
+
+    #[test]
+    fn test_scheduler() -> Result<()> {
+        let path = env::temp_dir()
+            .join(format!("dbl-reminders-{}.json", process::id()));
+        let start = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let callback = |fired: Rc<RefCell<Vec<String>>>| {
+            move |r: &Reminder| fired.borrow_mut().push(r.user_id.clone())
+        };
+
+        {
+            let mut scheduler = Scheduler::open(
+                &path,
+                clock.clone(),
+                callback(Rc::clone(&fired)),
+            )?;
+            let vote = |user_id: &str| Vote {
+                at: start,
+                bot: "1".to_owned(),
+                is_test: false,
+                is_weekend: false,
+                user_id: user_id.to_owned(),
+            };
+
+            assert!(scheduler.schedule_vote(&vote("2"))?.is_some());
+            assert!(scheduler.schedule_vote(&vote("3"))?.is_some());
+            assert!(scheduler.schedule_check("1", "4", false)?.is_none());
+
+            clock.advance(Duration::hours(1));
+            scheduler.schedule_check("1", "4", true)?;
+            scheduler.opt_out("3")?;
+            assert!(scheduler.schedule_vote(&vote("3"))?.is_none());
+
+            clock.advance(Duration::hours(11));
+            assert_eq!(scheduler.run_pending()?, 1);
+            assert_eq!(
+                scheduler.next_due(),
+                Some(start + Duration::hours(13)),
+            );
+        }
+
+        clock.advance(Duration::days(1));
+        let mut scheduler =
+            Scheduler::open(&path, clock, callback(Rc::clone(&fired)))?;
+        assert!(scheduler.is_opted_out("3"));
+        assert_eq!(scheduler.run_pending()?, 1);
+        assert_eq!(scheduler.run_pending()?, 0);
+        assert_eq!(*fired.borrow(), vec!["2", "4"]);
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+}
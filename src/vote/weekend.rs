@@ -0,0 +1,72 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
+use std::ops::Range;
+
+/// Predicts whether the weekend multiplier is in effect at the given time.
+///
+/// The multiplier is in effect from the start of Friday until the end of
+/// Sunday, in UTC. This doesn't account for the multiplier being changed by
+/// the service, so the [`get_weekend`] client method should be preferred to
+/// check whether it is in effect now.
+///
+/// [`get_weekend`]: ../bridge/struct.Client.html#method.get_weekend
+pub fn is_weekend(at: DateTime<Utc>) -> bool {
+    matches!(at.weekday(), Weekday::Fri | Weekday::Sat | Weekday::Sun)
+}
+
+/// Predicts the weekend multiplier's window which is in effect at the given
+/// time, or the next window if it isn't in effect.
+///
+/// Refer to [`is_weekend`] for when the multiplier is in effect.
+///
+/// # Examples
+///
+/// Announce the next double-vote weekend:
+///
+/// ```rust
+/// use chrono::Utc;
+/// use discord_bots_org::vote;
+///
+/// let weekend = vote::next_weekend(Utc::now());
+/// println!("Votes count double from {}!", weekend.start);
+/// ```
+///
+/// [`is_weekend`]: fn.is_weekend.html
+pub fn next_weekend(at: DateTime<Utc>) -> Range<DateTime<Utc>> {
+    let days_since_friday = at.weekday().days_since(Weekday::Fri);
+    let offset = match days_since_friday {
+        0..=2 => -i64::from(days_since_friday),
+        days => 7 - i64::from(days),
+    };
+
+    let date = at.date_naive() + Duration::days(offset);
+    let start = Utc.from_utc_datetime(&date.and_time(Default::default()));
+
+    start..start + Duration::days(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use super::{is_weekend, next_weekend};
+
+    #[test]
+    fn test_weekend() {
+        // 2019-01-04 is a Friday.
+        let friday = Utc.with_ymd_and_hms(2019, 1, 4, 0, 0, 0).unwrap();
+        let weekend = friday..friday + Duration::days(3);
+
+        for hours in &[-24 * 4, -1, 0, 30, 71, 72] {
+            let at = friday + Duration::hours(*hours);
+
+            assert_eq!(is_weekend(at), (0..72).contains(hours), "{}", at);
+        }
+
+        assert_eq!(next_weekend(friday - Duration::days(4)), weekend);
+        assert_eq!(next_weekend(friday - Duration::seconds(1)), weekend);
+        assert_eq!(next_weekend(friday + Duration::hours(71)), weekend);
+        assert_eq!(
+            next_weekend(friday + Duration::hours(72)).start,
+            friday + Duration::weeks(1),
+        );
+    }
+}
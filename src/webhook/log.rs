@@ -0,0 +1,400 @@
+use crate::{jsonl, model::Webhook, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// The length of a deduplication time bucket, in seconds.
+///
+/// This matches how often a user can vote for a bot.
+pub const DEDUP_BUCKET_SECS: i64 = 12 * 60 * 60;
+
+/// A webhook that was appended to an [`EventLog`].
+///
+/// [`EventLog`]: struct.EventLog.html
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    /// The deduplication key of the event.
+    ///
+    /// Refer to [`EventLog::dedup_key`] for how this is built.
+    ///
+    /// [`EventLog::dedup_key`]: struct.EventLog.html#method.dedup_key
+    pub key: String,
+    /// When the webhook was received.
+    pub received_at: DateTime<Utc>,
+    /// The webhook that was received.
+    #[serde(flatten)]
+    pub webhook: Webhook,
+}
+
+/// A line in the log file.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", tag = "record")]
+pub enum Record {
+    Ack {
+        key: String,
+    },
+    Event(Event),
+}
+
+/// A durable, append-only log of received webhooks.
+///
+/// Each webhook is appended as an [`Event`] with a deduplication key built
+/// from the bot, the user and a 12 hour time bucket, so a webhook that is
+/// retried by the service is only processed once. Once an event has been
+/// processed it is acknowledged, and events which were never acknowledged,
+/// such as because the process crashed, are [pending] when the log is
+/// reopened.
+///
+/// The log is stored as JSON lines, with each line flushed to disk before
+/// returning. Events in the log can be replayed with [`Sender::replay`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use discord_bots_org::webhook::EventLog;
+///
+/// let mut log = EventLog::open("votes.jsonl")?;
+///
+/// for event in log.pending() {
+///     println!("Redelivering vote by {}", event.webhook.user_id);
+/// }
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// [`Event`]: struct.Event.html
+/// [`Sender::replay`]: struct.Sender.html#method.replay
+/// [pending]: #method.pending
+#[derive(Debug)]
+pub struct EventLog {
+    /// The keys of events which have been acknowledged.
+    acked: HashSet<String>,
+    /// All events in the log, in order of being appended.
+    events: Vec<Event>,
+    /// The file the log is stored in, if any.
+    file: Option<(PathBuf, BufWriter<File>)>,
+    /// The keys of all events in the log.
+    keys: HashSet<String>,
+}
+
+impl EventLog {
+    /// Opens the log stored at the given path, creating it if it doesn't
+    /// exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file could not be read or created, or
+    /// [`Error::Json`] if it contains an invalid line. A final line which was
+    /// only partly written, such as because the process crashed, is removed
+    /// instead.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [`Error::Json`]: ../enum.Error.html#variant.Json
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let (records, file) = jsonl::open(path)?;
+        let mut log = Self::memory();
+
+        for record in records {
+            log.apply(record);
+        }
+
+        log.file = Some((path.to_owned(), BufWriter::new(file)));
+
+        Ok(log)
+    }
+
+    /// Creates a log which is only kept in memory.
+    ///
+    /// Webhooks are still deduplicated, but pending events are lost when the
+    /// process exits.
+    pub fn memory() -> Self {
+        Self {
+            acked: HashSet::new(),
+            events: Vec::new(),
+            file: None,
+            keys: HashSet::new(),
+        }
+    }
+
+    /// Builds the deduplication key of a webhook received at the given time.
+    ///
+    /// The key is made of the bot's ID, the user's ID and the 12 hour time
+    /// bucket of the time, such as `1:2:35792`.
+    pub fn dedup_key(webhook: &Webhook, received_at: DateTime<Utc>) -> String {
+        let bucket = received_at.timestamp().div_euclid(DEDUP_BUCKET_SECS);
+
+        format!("{}:{}:{}", webhook.bot, webhook.user_id, bucket)
+    }
+
+    /// Appends a webhook received at the given time.
+    ///
+    /// Returns the new event, or `None` if the webhook is a duplicate of an
+    /// event already in the log.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the event could not be written to disk.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn append(
+        &mut self,
+        webhook: Webhook,
+        received_at: DateTime<Utc>,
+    ) -> Result<Option<Event>> {
+        let key = Self::dedup_key(&webhook, received_at);
+
+        if self.keys.contains(&key) {
+            return Ok(None);
+        }
+
+        let event = Event {
+            key,
+            received_at,
+            webhook,
+        };
+
+        self.write(&Record::Event(event.clone()))?;
+        self.apply(Record::Event(event.clone()));
+
+        Ok(Some(event))
+    }
+
+    /// Acknowledges that the event with the given key has been processed.
+    ///
+    /// Acknowledging an unknown or already acknowledged event does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the acknowledgement could not be written to
+    /// disk.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn ack(&mut self, key: &str) -> Result<()> {
+        if !self.keys.contains(key) || self.acked.contains(key) {
+            return Ok(());
+        }
+
+        let record = Record::Ack {
+            key: key.to_owned(),
+        };
+
+        self.write(&record)?;
+        self.apply(record);
+
+        Ok(())
+    }
+
+    /// Whether the event with the given key has been acknowledged.
+    pub fn is_acked(&self, key: &str) -> bool {
+        self.acked.contains(key)
+    }
+
+    /// The events which have not been acknowledged, in the order they were
+    /// appended.
+    pub fn pending(&self) -> Vec<Event> {
+        self.events
+            .iter()
+            .filter(|event| !self.acked.contains(&event.key))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes acknowledged events which can no longer be duplicated, as they
+    /// are from before the previous time bucket, rewriting the log file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the log file could not be rewritten.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn compact(&mut self, now: DateTime<Utc>) -> Result<()> {
+        let cutoff = (now.timestamp().div_euclid(DEDUP_BUCKET_SECS) - 1)
+            * DEDUP_BUCKET_SECS;
+        let acked = &self.acked;
+
+        self.events.retain(|event| {
+            !acked.contains(&event.key)
+                || event.received_at.timestamp() >= cutoff
+        });
+
+        let keys = self
+            .events
+            .iter()
+            .map(|event| event.key.clone())
+            .collect::<HashSet<_>>();
+        self.acked.retain(|key| keys.contains(key));
+        self.keys = keys;
+
+        let path = match self.file.as_ref() {
+            Some((path, _)) => path.clone(),
+            None => return Ok(()),
+        };
+
+        let tmp = path.with_extension("compact");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+
+        for event in &self.events {
+            serde_json::to_writer(&mut writer, &Record::Event(event.clone()))?;
+            writer.write_all(b"\n")?;
+
+            if self.acked.contains(&event.key) {
+                serde_json::to_writer(&mut writer, &Record::Ack {
+                    key: event.key.clone(),
+                })?;
+                writer.write_all(b"\n")?;
+            }
+        }
+
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        fs::rename(&tmp, &path)?;
+
+        let file = OpenOptions::new().append(true).open(&path)?;
+        self.file = Some((path, BufWriter::new(file)));
+
+        Ok(())
+    }
+
+    fn apply(&mut self, record: Record) {
+        match record {
+            Record::Ack { key } => {
+                if self.keys.contains(&key) {
+                    self.acked.insert(key);
+                }
+            },
+            Record::Event(event) => {
+                self.keys.insert(event.key.clone());
+                self.events.push(event);
+            },
+        }
+    }
+
+    fn write(&mut self, record: &Record) -> Result<()> {
+        if let Some((_, writer)) = self.file.as_mut() {
+            serde_json::to_writer(&mut *writer, record)?;
+            writer.write_all(b"\n")?;
+            writer.flush()?;
+            writer.get_ref().sync_data()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        model::{Webhook, WebhookType},
+        Result,
+    };
+    use chrono::{Duration, TimeZone, Utc};
+    use std::{
+        env,
+        fs::{self, OpenOptions},
+        io::Write,
+        process,
+    };
+    use super::EventLog;
+
+    fn webhook(user_id: &str) -> Webhook {
+        Webhook {
+            bot: "1".to_owned(),
+            is_weekend: false,
+            kind: WebhookType::Upvote,
+            query: String::new(),
+            user_id: user_id.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_dedup() -> Result<()> {
+        let mut log = EventLog::memory();
+        let at = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+
+        assert!(log.append(webhook("2"), at)?.is_some());
+        assert!(log.append(webhook("2"), at + Duration::hours(1))?.is_none());
+        assert!(log.append(webhook("3"), at)?.is_some());
+        assert!(log.append(webhook("2"), at + Duration::hours(12))?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_redeliver() -> Result<()> {
+        let path = env::temp_dir()
+            .join(format!("dbl-log-{}.jsonl", process::id()));
+        let at = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+
+        {
+            let mut log = EventLog::open(&path)?;
+            let first = log.append(webhook("2"), at)?.unwrap();
+            log.append(webhook("3"), at)?;
+            log.ack(&first.key)?;
+        }
+
+        let mut log = EventLog::open(&path)?;
+        let pending = log.pending();
+
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].webhook.user_id, "3");
+        assert!(log.append(webhook("2"), at)?.is_none());
+
+        log.ack(&pending[0].key)?;
+        log.compact(at + Duration::days(2))?;
+        drop(log);
+
+        let log = EventLog::open(&path)?;
+        assert!(log.pending().is_empty());
+        assert!(fs::read_to_string(&path)?.is_empty());
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_torn_line() -> Result<()> {
+        let path = env::temp_dir()
+            .join(format!("dbl-log-torn-{}.jsonl", process::id()));
+        let at = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+
+        {
+            let mut log = EventLog::open(&path)?;
+            log.append(webhook("2"), at)?;
+        }
+
+        // A crash while appending leaves half a record.
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        file.write_all(br#"{"record":"event","key":"1:3:35"#)?;
+        drop(file);
+
+        {
+            let mut log = EventLog::open(&path)?;
+            assert_eq!(log.pending().len(), 1);
+            log.append(webhook("3"), at)?;
+        }
+
+        let log = EventLog::open(&path)?;
+        let users = log
+            .pending()
+            .into_iter()
+            .map(|event| event.webhook.user_id)
+            .collect::<Vec<_>>();
+        assert_eq!(users, ["2", "3"]);
+
+        // Invalid lines before the end are still an error.
+        let contents = fs::read_to_string(&path)?;
+        fs::write(&path, format!("{{\"record\"\n{}", contents))?;
+        assert!(EventLog::open(&path).is_err());
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+}
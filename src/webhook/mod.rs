@@ -0,0 +1,127 @@
+//! Utilities for working with incoming [`Webhook`]s.
+//!
+//! [`Webhook`]: ../model/struct.Webhook.html
+
+mod log;
+mod receiver;
+mod router;
+mod verify;
+#[cfg(feature = "reqwest-sync-support")]
+mod sender;
+#[cfg(feature = "tower-support")]
+mod service;
+
+pub use self::{
+    log::{Event, EventLog, DEDUP_BUCKET_SECS},
+    receiver::{Delivery, Outcome, Receiver},
+    router::Router,
+    verify::{Verification, Verifier, SIGNATURE_HEADER},
+};
+#[cfg(feature = "reqwest-sync-support")]
+pub use self::sender::Sender;
+#[cfg(feature = "tower-support")]
+pub use self::service::{WebhookLayer, WebhookMiddleware, WebhookService};
+
+use crate::{model::Webhook, Result};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use self::log::Record;
+
+/// The body of an upvote for bot 1 by user 2, shared by tests.
+#[cfg(test)]
+const TEST_BODY: &str = concat!(
+    r#"{"bot":"1","user":"2","type":"upvote","isWeekend":false,"#,
+    r#""query":""}"#,
+);
+
+/// A webhook along with when it was received, as stored in a JSON-lines
+/// recording.
+///
+/// The webhook's fields are flattened, so a line containing only a
+/// [`Webhook`] is also a valid recording without a timestamp.
+///
+/// [`Webhook`]: ../model/struct.Webhook.html
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedWebhook {
+    /// When the webhook was received, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<DateTime<FixedOffset>>,
+    /// The webhook that was received.
+    #[serde(flatten)]
+    pub webhook: Webhook,
+}
+
+/// Reads a JSON-lines recording of webhooks, skipping blank lines.
+///
+/// An [`EventLog`]'s file is also a valid recording, as its
+/// acknowledgements are skipped.
+///
+/// # Errors
+///
+/// Each item is an [`Error::Io`] if a line could not be read, or an
+/// [`Error::Json`] if it is not a valid [`RecordedWebhook`].
+///
+/// [`Error::Io`]: ../enum.Error.html#variant.Io
+/// [`Error::Json`]: ../enum.Error.html#variant.Json
+/// [`EventLog`]: struct.EventLog.html
+/// [`RecordedWebhook`]: struct.RecordedWebhook.html
+pub fn read_recording(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<RecordedWebhook>> {
+    reader.lines().filter_map(|line| {
+        let line = match line {
+            Ok(line) => line,
+            Err(why) => return Some(Err(why.into())),
+        };
+
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        match serde_json::from_str::<Record>(&line) {
+            Ok(Record::Ack { .. }) => None,
+            Ok(Record::Event(event)) => Some(Ok(RecordedWebhook {
+                received_at: Some(event.received_at.into()),
+                webhook: event.webhook,
+            })),
+            Err(_) => Some(serde_json::from_str(&line).map_err(From::from)),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use super::{read_recording, TEST_BODY};
+
+    #[test]
+    fn test_read_recording() -> Result<()> {
+        let recording = TEST_BODY.to_owned() + concat!(
+            "\n\n",
+            r#"{"receivedAt":"2019-01-01T00:00:00Z","bot":"1","user":"3","#,
+            r#""type":"test","isWeekend":true,"query":"?a=1"}"#,
+            "\n",
+            r#"{ "key": "1:3:35792", "record" : "ack" }"#,
+            "\n",
+            r#"{"record":"event","key":"1:4:35792","#,
+            r#""receivedAt":"2019-01-01T00:00:00Z","bot":"1","user":"4","#,
+            r#""type":"upvote","isWeekend":false,"query":""}"#,
+            "\n",
+        );
+        let webhooks = read_recording(recording.as_bytes())
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(webhooks.len(), 3);
+        assert!(webhooks[0].received_at.is_none());
+        assert_eq!(webhooks[1].webhook.user_id, "3");
+        assert_eq!(
+            webhooks[1].received_at.map(|at| at.timestamp()),
+            Some(1_546_300_800),
+        );
+        assert_eq!(webhooks[2].webhook.user_id, "4");
+
+        Ok(())
+    }
+}
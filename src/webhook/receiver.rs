@@ -0,0 +1,293 @@
+use crate::{model::Webhook, Result};
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex, MutexGuard};
+use super::{
+    log::{Event, EventLog},
+    verify::Verifier,
+};
+
+/// The outcome of receiving a webhook request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The body was not a valid webhook.
+    BadRequest,
+    /// The webhook was delivered to the handler.
+    Delivered,
+    /// The webhook is a duplicate of one already received, and was not
+    /// delivered again.
+    Duplicate,
+    /// The request's signature or authorization could not be verified.
+    Unauthorized,
+    /// The webhook is for a bot which has no route.
+    UnknownBot,
+}
+
+impl Outcome {
+    /// The HTTP status code to respond to the request with.
+    ///
+    /// Duplicates are responded to with a success so that the service stops
+    /// retrying them.
+    pub fn status(self) -> u16 {
+        match self {
+            Outcome::BadRequest => 400,
+            Outcome::Delivered | Outcome::Duplicate => 200,
+            Outcome::Unauthorized => 401,
+            Outcome::UnknownBot => 404,
+        }
+    }
+}
+
+/// An event delivered to a [`Receiver`]'s handler.
+///
+/// The event must be [acknowledged] once it has been processed. An event
+/// which is dropped without being acknowledged stays pending, and is
+/// redelivered by [`Receiver::redeliver`].
+///
+/// [`Receiver`]: struct.Receiver.html
+/// [`Receiver::redeliver`]: struct.Receiver.html#method.redeliver
+/// [acknowledged]: #method.ack
+#[derive(Debug)]
+pub struct Delivery {
+    event: Event,
+    log: Arc<Mutex<EventLog>>,
+}
+
+impl Delivery {
+    /// The delivered event.
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    /// The delivered event's webhook.
+    pub fn webhook(&self) -> &Webhook {
+        &self.event.webhook
+    }
+
+    /// Acknowledges that the event has been processed, so that it is not
+    /// redelivered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the acknowledgement could not be written to
+    /// the log.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn ack(self) -> Result<()> {
+        lock(&self.log).ack(&self.event.key)
+    }
+}
+
+/// A transport-agnostic receiver of webhook requests.
+///
+/// Requests are checked with a [`Verifier`], and each new webhook is
+/// appended to an [`EventLog`] before being delivered to the handler, so
+/// retried webhooks are only delivered once.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use discord_bots_org::webhook::{EventLog, Receiver};
+///
+/// let log = EventLog::open("votes.jsonl")?;
+/// let receiver = Receiver::new("secret", log, |delivery| {
+///     println!("{} voted", delivery.webhook().user_id);
+///
+///     if let Err(why) = delivery.ack() {
+///         eprintln!("Failed to acknowledge vote: {}", why);
+///     }
+/// });
+///
+/// // Redeliver votes which weren't processed before the last shutdown.
+/// receiver.redeliver();
+///
+/// // Then, in the HTTP server's request handler:
+/// # let (authorization, body) = (None, &b""[..]);
+/// # let signature = None;
+/// let outcome = receiver.receive(authorization, signature, body)?;
+/// println!("Responding with {}", outcome.status());
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// [`EventLog`]: struct.EventLog.html
+/// [`Verifier`]: struct.Verifier.html
+pub struct Receiver<H> {
+    handler: H,
+    log: Arc<Mutex<EventLog>>,
+    verifier: Verifier,
+}
+
+impl<H: Fn(Delivery)> Receiver<H> {
+    /// Creates a new receiver accepting requests signed or authorized with
+    /// the webhook's secret, appending webhooks to the given log.
+    ///
+    /// Both signed and legacy requests are accepted. Use [`with_verifier`]
+    /// to configure how requests are verified.
+    ///
+    /// [`with_verifier`]: #method.with_verifier
+    pub fn new(secret: impl Into<String>, log: EventLog, handler: H) -> Self {
+        Self::with_verifier(Verifier::new(secret), log, handler)
+    }
+
+    /// Creates a new receiver accepting requests verified by a verifier,
+    /// appending webhooks to the given log.
+    pub fn with_verifier(
+        verifier: Verifier,
+        log: EventLog,
+        handler: H,
+    ) -> Self {
+        Self {
+            handler,
+            log: Arc::new(Mutex::new(log)),
+            verifier,
+        }
+    }
+
+    /// Receives a webhook request with the given `Authorization` header,
+    /// [signature header] and body, delivering the webhook to the handler if
+    /// it is new.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the webhook could not be written to the log.
+    /// The request should then be responded to with a server error, so that
+    /// the service retries it.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [signature header]: constant.SIGNATURE_HEADER.html
+    pub fn receive(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> Result<Outcome> {
+        self.receive_at(authorization, signature, body, Utc::now())
+    }
+
+    /// Receives a webhook request as if it was received at the given time.
+    ///
+    /// Refer to [`receive`] for more information.
+    ///
+    /// [`receive`]: #method.receive
+    pub fn receive_at(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+        received_at: DateTime<Utc>,
+    ) -> Result<Outcome> {
+        if !self.is_verified(authorization, signature, body, received_at) {
+            return Ok(Outcome::Unauthorized);
+        }
+
+        match serde_json::from_slice(body) {
+            Ok(webhook) => self.accept(webhook, received_at),
+            Err(_) => Ok(Outcome::BadRequest),
+        }
+    }
+
+    /// Redelivers events in the log which have not been acknowledged,
+    /// returning how many were redelivered.
+    ///
+    /// This should be called once on startup.
+    pub fn redeliver(&self) -> usize {
+        let pending = lock(&self.log).pending();
+        let count = pending.len();
+
+        for event in pending {
+            self.deliver(event);
+        }
+
+        count
+    }
+
+    pub(super) fn is_verified(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.verifier
+            .verify_at(authorization, signature, body, now)
+            .is_verified()
+    }
+
+    /// Appends an already verified webhook to the log, delivering it if it
+    /// is new.
+    pub(super) fn accept(
+        &self,
+        webhook: Webhook,
+        received_at: DateTime<Utc>,
+    ) -> Result<Outcome> {
+        let event = lock(&self.log).append(webhook, received_at)?;
+
+        Ok(match event {
+            Some(event) => {
+                self.deliver(event);
+
+                Outcome::Delivered
+            },
+            None => Outcome::Duplicate,
+        })
+    }
+
+    fn deliver(&self, event: Event) {
+        (self.handler)(Delivery {
+            event,
+            log: Arc::clone(&self.log),
+        });
+    }
+}
+
+fn lock(log: &Mutex<EventLog>) -> MutexGuard<'_, EventLog> {
+    log.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use chrono::{TimeZone, Utc};
+    use std::sync::Mutex;
+    use super::{
+        super::{EventLog, TEST_BODY},
+        Delivery,
+        Outcome,
+        Receiver,
+    };
+
+    #[test]
+    fn test_receive() -> Result<()> {
+        let deliveries = Mutex::new(Vec::new());
+        let receiver = Receiver::new("auth", EventLog::memory(), |delivery| {
+            deliveries.lock().unwrap().push(delivery);
+        });
+        let at = Utc.timestamp_opt(1_546_300_800, 0).unwrap();
+        let body = TEST_BODY.as_bytes();
+
+        assert_eq!(
+            receiver.receive_at(Some("bad"), None, body, at)?,
+            Outcome::Unauthorized,
+        );
+        assert_eq!(
+            receiver.receive_at(Some("auth"), None, b"{}", at)?,
+            Outcome::BadRequest,
+        );
+        assert_eq!(
+            receiver.receive_at(Some("auth"), None, body, at)?,
+            Outcome::Delivered,
+        );
+        assert_eq!(
+            receiver.receive_at(Some("auth"), None, body, at)?,
+            Outcome::Duplicate,
+        );
+
+        let delivery: Delivery = deliveries.lock().unwrap().remove(0);
+        assert_eq!(delivery.webhook().user_id, "2");
+        assert_eq!(receiver.redeliver(), 1);
+
+        delivery.ack()?;
+        assert_eq!(receiver.redeliver(), 0);
+
+        Ok(())
+    }
+}
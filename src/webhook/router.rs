@@ -0,0 +1,265 @@
+use crate::{model::Webhook, Result};
+use chrono::Utc;
+use std::collections::HashMap;
+use super::receiver::{Delivery, Outcome, Receiver};
+
+/// Routes webhook requests for multiple bots to a [`Receiver`] per bot.
+///
+/// Each bot's receiver has its own secret and handler. Requests can
+/// be routed either by the bot ID in the webhook's body, for a single shared
+/// endpoint, or by the bot ID in the request's path, such as
+/// `/votes/{bot_id}`. Requests for bots without a route are rejected with
+/// [`Outcome::UnknownBot`].
+///
+/// Requests are authenticated before anything else about them is revealed,
+/// so requests which aren't verified by any route's receiver are always
+/// rejected with [`Outcome::Unauthorized`]. This keeps unauthenticated
+/// callers from discovering which bots are routed.
+///
+/// As each bot's handler is usually a different closure, they can be boxed
+/// to be stored in the same router.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use discord_bots_org::webhook::{Delivery, EventLog, Receiver, Router};
+///
+/// let mut router = Router::<Box<dyn Fn(Delivery)>>::new();
+/// router.route("270198738570444801", Receiver::new(
+///     "first secret",
+///     EventLog::open("first.jsonl")?,
+///     Box::new(|delivery| {
+///         println!("First bot voted by {}", delivery.webhook().user_id);
+///         let _ = delivery.ack();
+///     }),
+/// ));
+/// router.route("159985870458322944", Receiver::new(
+///     "second secret",
+///     EventLog::open("second.jsonl")?,
+///     Box::new(|delivery| {
+///         println!("Second bot voted by {}", delivery.webhook().user_id);
+///         let _ = delivery.ack();
+///     }),
+/// ));
+///
+/// router.redeliver();
+///
+/// // Then, in the HTTP server's request handler:
+/// # let (path, authorization, signature, body) = ("", None, None, &b""[..]);
+/// let outcome = router.receive_path(path, authorization, signature, body)?;
+/// println!("Responding with {}", outcome.status());
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+///
+/// [`Outcome::Unauthorized`]: enum.Outcome.html#variant.Unauthorized
+/// [`Outcome::UnknownBot`]: enum.Outcome.html#variant.UnknownBot
+/// [`Receiver`]: struct.Receiver.html
+pub struct Router<H> {
+    routes: HashMap<String, Receiver<H>>,
+}
+
+impl<H: Fn(Delivery)> Router<H> {
+    /// Creates a new router without any routes.
+    pub fn new() -> Self {
+        Self {
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Routes webhooks for the bot with the given ID to a receiver,
+    /// replacing any existing route for the bot.
+    pub fn route(
+        &mut self,
+        bot_id: impl Into<String>,
+        receiver: Receiver<H>,
+    ) -> &mut Self {
+        self.routes.insert(bot_id.into(), receiver);
+
+        self
+    }
+
+    /// Receives a webhook request, routing it by the bot ID in its body.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the webhook could not be written to the
+    /// receiver's log.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn receive(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> Result<Outcome> {
+        let now = Utc::now();
+        let verified = |receiver: &Receiver<H>| {
+            receiver.is_verified(authorization, signature, body, now)
+        };
+        let webhook = match serde_json::from_slice::<Webhook>(body) {
+            Ok(webhook) => webhook,
+            Err(_) => return Ok(self.reject(Outcome::BadRequest, verified)),
+        };
+
+        let receiver = match self.routes.get(&webhook.bot) {
+            Some(receiver) => receiver,
+            None => return Ok(self.reject(Outcome::UnknownBot, verified)),
+        };
+
+        if !verified(receiver) {
+            return Ok(Outcome::Unauthorized);
+        }
+
+        receiver.accept(webhook, now)
+    }
+
+    /// Receives a webhook request, routing it by the bot ID in the last
+    /// segment of the request's path, such as `/votes/270198738570444801`.
+    ///
+    /// The request is rejected with [`Outcome::BadRequest`] if the bot ID in
+    /// its body doesn't match the one in its path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the webhook could not be written to the
+    /// receiver's log.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [`Outcome::BadRequest`]: enum.Outcome.html#variant.BadRequest
+    pub fn receive_path(
+        &self,
+        path: &str,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> Result<Outcome> {
+        let now = Utc::now();
+        let path = path.split('?').next().unwrap_or_default();
+        let bot_id = path.trim_end_matches('/').rsplit('/').next();
+
+        let verified = |receiver: &Receiver<H>| {
+            receiver.is_verified(authorization, signature, body, now)
+        };
+
+        let receiver = match bot_id.and_then(|id| self.routes.get(id)) {
+            Some(receiver) => receiver,
+            None => return Ok(self.reject(Outcome::UnknownBot, verified)),
+        };
+
+        if !verified(receiver) {
+            return Ok(Outcome::Unauthorized);
+        }
+
+        match serde_json::from_slice::<Webhook>(body) {
+            Ok(ref webhook) if Some(webhook.bot.as_str()) != bot_id => {
+                Ok(Outcome::BadRequest)
+            },
+            Ok(webhook) => receiver.accept(webhook, now),
+            Err(_) => Ok(Outcome::BadRequest),
+        }
+    }
+
+    /// Redelivers unacknowledged events for all routes, returning how many
+    /// were redelivered.
+    ///
+    /// This should be called once on startup.
+    pub fn redeliver(&self) -> usize {
+        self.routes.values().map(Receiver::redeliver).sum()
+    }
+
+    /// Rejects a request which couldn't be routed with an outcome, or with
+    /// [`Outcome::Unauthorized`] if no route's receiver verifies it.
+    ///
+    /// [`Outcome::Unauthorized`]: enum.Outcome.html#variant.Unauthorized
+    fn reject(
+        &self,
+        outcome: Outcome,
+        verified: impl Fn(&Receiver<H>) -> bool,
+    ) -> Outcome {
+        if self.routes.values().any(verified) {
+            outcome
+        } else {
+            Outcome::Unauthorized
+        }
+    }
+}
+
+impl<H: Fn(Delivery)> Default for Router<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use std::{cell::RefCell, rc::Rc};
+    use super::{
+        super::{Delivery, EventLog, Outcome, Receiver},
+        Router,
+    };
+
+    fn body(bot: &str) -> Vec<u8> {
+        format!(
+            concat!(
+                r#"{{"bot":"{}","user":"3","type":"upvote","#,
+                r#""isWeekend":false,"query":""}}"#,
+            ),
+            bot,
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_routing() -> Result<()> {
+        let delivered = Rc::new(RefCell::new(Vec::new()));
+        let mut router = Router::<Box<dyn Fn(Delivery)>>::new();
+
+        for (bot, auth) in &[("1", "one"), ("2", "two")] {
+            let delivered = Rc::clone(&delivered);
+            router.route(*bot, Receiver::new(
+                *auth,
+                EventLog::memory(),
+                Box::new(move |delivery: Delivery| {
+                    let bot = delivery.webhook().bot.clone();
+                    delivered.borrow_mut().push(bot);
+                }),
+            ));
+        }
+
+        let auth = Some("one");
+        let receive = |body: &[u8]| router.receive(auth, None, body);
+        assert_eq!(receive(&body("1"))?, Outcome::Delivered);
+        assert_eq!(receive(&body("2"))?, Outcome::Unauthorized);
+        assert_eq!(receive(&body("9"))?, Outcome::UnknownBot);
+        assert_eq!(receive(b"")?, Outcome::BadRequest);
+
+        // Unauthenticated requests can't tell which bots are routed.
+        let receive = |body: &[u8]| router.receive(Some("nope"), None, body);
+        assert_eq!(receive(&body("1"))?, Outcome::Unauthorized);
+        assert_eq!(receive(&body("9"))?, Outcome::Unauthorized);
+        assert_eq!(receive(b"")?, Outcome::Unauthorized);
+        assert_eq!(
+            router.receive_path("/votes/9", None, None, &body("9"))?,
+            Outcome::Unauthorized,
+        );
+
+        assert_eq!(
+            router.receive_path("/votes/2/", Some("two"), None, &body("2"))?,
+            Outcome::Delivered,
+        );
+        assert_eq!(
+            router.receive_path("/votes/2", Some("two"), None, &body("1"))?,
+            Outcome::BadRequest,
+        );
+        assert_eq!(
+            router.receive_path("/votes/9?a=1", Some("two"), None, &body("9"))?,
+            Outcome::UnknownBot,
+        );
+
+        assert_eq!(*delivered.borrow(), vec!["1", "2"]);
+        assert_eq!(router.redeliver(), 2);
+
+        Ok(())
+    }
+}
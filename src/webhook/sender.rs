@@ -0,0 +1,221 @@
+use chrono::{DateTime, FixedOffset};
+use crate::{model::Webhook, Error, Result};
+use reqwest::{
+    blocking::{Client as ReqwestClient, Response},
+    header::{AUTHORIZATION, HeaderValue},
+    StatusCode,
+    Url,
+};
+use std::{io::BufRead, sync::Arc, thread, time::Duration};
+use super::read_recording;
+
+/// Sends webhooks to an endpoint, to test webhook handlers without waiting
+/// for real votes.
+///
+/// # Examples
+///
+/// Send a test vote to a local webhook handler:
+///
+/// ```rust,no_run
+/// use discord_bots_org::{
+///     model::{Webhook, WebhookType},
+///     webhook::Sender,
+/// };
+/// use reqwest::blocking::Client as ReqwestClient;
+/// use std::sync::Arc;
+///
+/// let client = Arc::new(ReqwestClient::new());
+/// let sender = Sender::new(client, "http://localhost:8080/votes", "secret")?;
+///
+/// sender.send(&Webhook {
+///     bot: "270198738570444801".to_owned(),
+///     is_weekend: false,
+///     kind: WebhookType::Test,
+///     query: "?campaign=launch".to_owned(),
+///     user_id: "114941315417899012".to_owned(),
+/// })?;
+/// # Ok::<(), discord_bots_org::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sender {
+    auth: String,
+    inner: Arc<ReqwestClient>,
+    url: Url,
+}
+
+impl Sender {
+    /// Creates a new sender which posts webhooks to the given URL, with the
+    /// given value as the `Authorization` header.
+    ///
+    /// This accepts an existing reqwest Client so a single HTTP client may be
+    /// shared across your application.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidUrl`] if the URL is invalid.
+    ///
+    /// [`Error::InvalidUrl`]: ../enum.Error.html#variant.InvalidUrl
+    pub fn new(
+        reqwest_client: Arc<ReqwestClient>,
+        url: impl AsRef<str>,
+        auth: impl Into<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            auth: auth.into(),
+            inner: reqwest_client,
+            url: Url::parse(url.as_ref())?,
+        })
+    }
+
+    /// Sends a webhook.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ReqwestUnauthorized`] if the endpoint responds with a
+    /// 401 or 403, [`Error::ReqwestBad`] if it responds with a 400, or
+    /// [`Error::ReqwestInvalid`] if it responds with any other unsuccessful
+    /// status.
+    ///
+    /// [`Error::ReqwestBad`]: ../enum.Error.html#variant.ReqwestBad
+    /// [`Error::ReqwestInvalid`]: ../enum.Error.html#variant.ReqwestInvalid
+    /// [`Error::ReqwestUnauthorized`]: ../enum.Error.html#variant.ReqwestUnauthorized
+    pub fn send(&self, webhook: &Webhook) -> Result<()> {
+        let resp = self
+            .inner
+            .post(self.url.clone())
+            .header(AUTHORIZATION, HeaderValue::from_str(&self.auth)?)
+            .json(webhook)
+            .send()?;
+
+        check(resp)
+    }
+
+    /// Replays a JSON-lines recording of webhooks, as read by
+    /// [`read_recording`], returning the number of webhooks sent.
+    ///
+    /// The time between webhooks with a recorded [`received_at`] is kept,
+    /// divided by `speed`. A speed of `2.0` replays twice as fast, and a speed
+    /// of `f64::INFINITY` sends every webhook immediately. Webhooks without a
+    /// recorded time are sent immediately.
+    ///
+    /// Replaying stops at the first webhook that can't be read or sent.
+    ///
+    /// [`read_recording`]: fn.read_recording.html
+    /// [`received_at`]: struct.RecordedWebhook.html#structfield.received_at
+    pub fn replay(&self, reader: impl BufRead, speed: f64) -> Result<usize> {
+        let mut previous: Option<DateTime<FixedOffset>> = None;
+        let mut sent = 0;
+
+        for recorded in read_recording(reader) {
+            let recorded = recorded?;
+
+            if let (Some(previous), Some(current)) =
+                (previous, recorded.received_at)
+            {
+                let gap = (current - previous).to_std().unwrap_or_default();
+                let delay = gap.as_secs_f64() / speed;
+
+                if delay.is_finite() && delay > 0.0 {
+                    thread::sleep(Duration::from_secs_f64(delay));
+                }
+            }
+
+            previous = recorded.received_at.or(previous);
+            self.send(&recorded.webhook)?;
+            sent += 1;
+        }
+
+        Ok(sent)
+    }
+}
+
+fn check(resp: Response) -> Result<()> {
+    match resp.status() {
+        status if status.is_success() => Ok(()),
+        StatusCode::BAD_REQUEST => Err(Error::ReqwestBad(Box::new(resp))),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Err(Error::ReqwestUnauthorized(Box::new(resp)))
+        },
+        _ => Err(Error::ReqwestInvalid(Box::new(resp))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{mock::MockServer, model::Webhook, Error, Result};
+    use reqwest::blocking::Client as ReqwestClient;
+    use std::{sync::Arc, time::Duration};
+    use super::{super::TEST_BODY, Sender};
+
+    fn sender(server: &MockServer) -> Result<Sender> {
+        let url = format!("{}/votes", server.url());
+
+        Sender::new(Arc::new(ReqwestClient::new()), url, "secret")
+    }
+
+    #[test]
+    fn test_send() -> Result<()> {
+        let server = MockServer::start_paths(&[("POST", "/votes", 200, "")]);
+        let webhook = serde_json::from_str::<Webhook>(TEST_BODY)?;
+
+        sender(&server)?.send(&webhook)?;
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/votes");
+        assert_eq!(requests[0].authorization.as_deref(), Some("secret"));
+        let body = serde_json::from_str::<Webhook>(&requests[0].body)?;
+        assert_eq!(body.user_id, "2");
+        assert_eq!(body.bot, "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay() -> Result<()> {
+        let server = MockServer::start_paths(&[("POST", "/votes", 200, "")]);
+        let recording = concat!(
+            r#"{"receivedAt":"2019-01-01T00:00:00Z","bot":"1","user":"2","#,
+            r#""type":"upvote","isWeekend":false,"query":""}"#,
+            "\n",
+            r#"{"receivedAt":"2019-01-01T00:00:00.4Z","bot":"1","user":"3","#,
+            r#""type":"upvote","isWeekend":false,"query":""}"#,
+            "\n",
+            r#"{"bot":"1","user":"4","type":"test","isWeekend":false,"#,
+            r#""query":""}"#,
+            "\n",
+        );
+
+        let sent = sender(&server)?.replay(recording.as_bytes(), 2.0)?;
+        assert_eq!(sent, 3);
+
+        let requests = server.requests();
+        let users = requests
+            .iter()
+            .map(|request| serde_json::from_str::<Webhook>(&request.body))
+            .map(|webhook| Ok(webhook?.user_id))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(users, ["2", "3", "4"]);
+        assert!(requests.iter().all(|r| r.authorization.is_some()));
+
+        // The 400ms gap is halved, and untimed webhooks are sent immediately.
+        let gap = requests[1].received_at - requests[0].received_at;
+        assert!(gap >= Duration::from_millis(200));
+        assert!(gap < Duration::from_millis(400));
+        let gap = requests[2].received_at - requests[1].received_at;
+        assert!(gap < Duration::from_millis(200));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_send_unauthorized() -> Result<()> {
+        let server = MockServer::start_paths(&[("POST", "/votes", 401, "")]);
+        let webhook = serde_json::from_str::<Webhook>(TEST_BODY)?;
+
+        match sender(&server)?.send(&webhook) {
+            Err(Error::ReqwestUnauthorized(_)) => Ok(()),
+            other => panic!("expected a 401, got {:?}", other),
+        }
+    }
+}
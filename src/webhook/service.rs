@@ -0,0 +1,472 @@
+use bytes::Bytes;
+use futures_channel::oneshot;
+use http::{header::AUTHORIZATION, Method, Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use std::{
+    convert::Infallible,
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use super::{
+    receiver::{Delivery, Outcome, Receiver},
+    verify::SIGNATURE_HEADER,
+};
+use tower::{Layer, Service};
+
+type BoxFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send>>;
+type Job = Box<dyn FnOnce() + Send>;
+type Spawner = Arc<dyn Fn(Job) + Send + Sync>;
+
+/// The largest request body which is read, in bytes.
+///
+/// Webhook bodies are much smaller, so larger bodies are rejected before
+/// they are verified.
+const MAX_BODY_LEN: usize = 64 * 1024;
+
+/// A [`tower::Service`] which receives webhook requests with a [`Receiver`].
+///
+/// Responses have an empty body, with a status code from the request's
+/// [`Outcome`], or a 500 if the webhook could not be written to the log.
+/// Requests with a body larger than 64 KiB are responded to with a 413
+/// without being read further.
+///
+/// Receiving a webhook is blocking: it locks the receiver's log, writes the
+/// webhook to disk and calls the handler. By default this happens within the
+/// request's future, blocking the executor thread polling it. Set a
+/// [`spawner`], such as tokio's `spawn_blocking`, to run it on another
+/// thread instead.
+///
+/// # Examples
+///
+/// Serve webhooks with hyper:
+///
+/// ```rust,ignore
+/// use discord_bots_org::webhook::{EventLog, Receiver, WebhookService};
+///
+/// let log = EventLog::open("votes.jsonl")?;
+/// let receiver = Receiver::new("secret", log, |delivery| {
+///     println!("{} voted", delivery.webhook().user_id);
+///     let _ = delivery.ack();
+/// });
+/// receiver.redeliver();
+///
+/// let mut service = WebhookService::new(receiver);
+/// service.spawner(|job| {
+///     tokio::task::spawn_blocking(job);
+/// });
+/// // Serve `service` with `hyper_util::service::TowerToHyperService`.
+/// ```
+///
+/// [`Outcome`]: enum.Outcome.html
+/// [`Receiver`]: struct.Receiver.html
+/// [`spawner`]: #method.spawner
+/// [`tower::Service`]: https://docs.rs/tower/0.5/tower/trait.Service.html
+pub struct WebhookService<H> {
+    receiver: Arc<Receiver<H>>,
+    spawner: Option<Spawner>,
+}
+
+impl<H> WebhookService<H> {
+    /// Creates a new service receiving webhooks with a receiver.
+    pub fn new(receiver: Receiver<H>) -> Self {
+        Self::from_arc(Arc::new(receiver))
+    }
+
+    /// Creates a new service receiving webhooks with a shared receiver.
+    pub fn from_arc(receiver: Arc<Receiver<H>>) -> Self {
+        Self {
+            receiver,
+            spawner: None,
+        }
+    }
+
+    /// Sets a function to run the blocking work of receiving webhooks on
+    /// another thread, such as with tokio's `spawn_blocking`.
+    ///
+    /// If the function drops a job without running it, the request is
+    /// responded to with a 500.
+    pub fn spawner<F>(&mut self, spawner: F) -> &mut Self
+    where
+        F: Fn(Box<dyn FnOnce() + Send>) + Send + Sync + 'static,
+    {
+        self.spawner = Some(Arc::new(spawner));
+
+        self
+    }
+}
+
+impl<H> Clone for WebhookService<H> {
+    fn clone(&self) -> Self {
+        Self {
+            receiver: Arc::clone(&self.receiver),
+            spawner: self.spawner.clone(),
+        }
+    }
+}
+
+impl<B, H> Service<Request<B>> for WebhookService<H>
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn Error + Send + Sync>>,
+    H: Fn(Delivery) + Send + Sync + 'static,
+{
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let receiver = Arc::clone(&self.receiver);
+        let spawner = self.spawner.clone();
+
+        Box::pin(async move {
+            Ok(receive(receiver, spawner.as_ref(), request).await)
+        })
+    }
+}
+
+/// A [`tower::Layer`] which receives webhook requests to a path with a
+/// [`Receiver`], passing all other requests to the inner service.
+///
+/// Only `POST` requests to the path are received as webhooks. Responses to
+/// webhook requests are created with the inner service's default response
+/// body and a status code, as with [`WebhookService`].
+///
+/// As with [`WebhookService`], receiving a webhook blocks the executor thread
+/// polling the request unless a [`spawner`] is set.
+///
+/// # Examples
+///
+/// Mount the webhook endpoint in front of an existing service:
+///
+/// ```rust,ignore
+/// use discord_bots_org::webhook::{EventLog, Receiver, WebhookLayer};
+/// use tower::ServiceBuilder;
+///
+/// let log = EventLog::open("votes.jsonl")?;
+/// let receiver = Receiver::new("secret", log, |delivery| {
+///     let _ = delivery.ack();
+/// });
+///
+/// let service = ServiceBuilder::new()
+///     .layer(WebhookLayer::new("/votes", receiver))
+///     .service(app);
+/// ```
+///
+/// [`Receiver`]: struct.Receiver.html
+/// [`WebhookService`]: struct.WebhookService.html
+/// [`spawner`]: #method.spawner
+/// [`tower::Layer`]: https://docs.rs/tower/0.5/tower/trait.Layer.html
+pub struct WebhookLayer<H> {
+    path: Arc<str>,
+    receiver: Arc<Receiver<H>>,
+    spawner: Option<Spawner>,
+}
+
+impl<H> WebhookLayer<H> {
+    /// Creates a new layer receiving webhooks sent to the given path.
+    pub fn new(path: impl Into<String>, receiver: Receiver<H>) -> Self {
+        Self {
+            path: path.into().into(),
+            receiver: Arc::new(receiver),
+            spawner: None,
+        }
+    }
+
+    /// Sets a function to run the blocking work of receiving webhooks on
+    /// another thread.
+    ///
+    /// Refer to [`WebhookService::spawner`] for more information.
+    ///
+    /// [`WebhookService::spawner`]: struct.WebhookService.html#method.spawner
+    pub fn spawner<F>(&mut self, spawner: F) -> &mut Self
+    where
+        F: Fn(Box<dyn FnOnce() + Send>) + Send + Sync + 'static,
+    {
+        self.spawner = Some(Arc::new(spawner));
+
+        self
+    }
+}
+
+impl<H> Clone for WebhookLayer<H> {
+    fn clone(&self) -> Self {
+        Self {
+            path: Arc::clone(&self.path),
+            receiver: Arc::clone(&self.receiver),
+            spawner: self.spawner.clone(),
+        }
+    }
+}
+
+impl<H, S> Layer<S> for WebhookLayer<H> {
+    type Service = WebhookMiddleware<H, S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        WebhookMiddleware {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service created by a [`WebhookLayer`].
+///
+/// [`WebhookLayer`]: struct.WebhookLayer.html
+pub struct WebhookMiddleware<H, S> {
+    inner: S,
+    layer: WebhookLayer<H>,
+}
+
+impl<H, S: Clone> Clone for WebhookMiddleware<H, S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+impl<B, H, S, ResBody> Service<Request<B>> for WebhookMiddleware<H, S>
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn Error + Send + Sync>>,
+    H: Fn(Delivery) + Send + Sync + 'static,
+    S: Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let is_webhook = request.method() == Method::POST
+            && request.uri().path() == &*self.layer.path;
+
+        if !is_webhook {
+            return Box::pin(self.inner.call(request));
+        }
+
+        let receiver = Arc::clone(&self.layer.receiver);
+        let spawner = self.layer.spawner.clone();
+
+        Box::pin(async move {
+            let response = receive(receiver, spawner.as_ref(), request).await;
+
+            Ok(response.map(|_| ResBody::default()))
+        })
+    }
+}
+
+async fn receive<B, H>(
+    receiver: Arc<Receiver<H>>,
+    spawner: Option<&Spawner>,
+    request: Request<B>,
+) -> Response<Full<Bytes>>
+where
+    B: Body,
+    B::Error: Into<Box<dyn Error + Send + Sync>>,
+    H: Fn(Delivery) + Send + Sync + 'static,
+{
+    let (parts, body) = request.into_parts();
+    let header = |name| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    let authorization = header(AUTHORIZATION.as_str());
+    let signature = header(SIGNATURE_HEADER);
+
+    let status = match Limited::new(body, MAX_BODY_LEN).collect().await {
+        Ok(body) => {
+            let body = body.to_bytes();
+            let work = move || {
+                let authorization = authorization.as_deref();
+                let signature = signature.as_deref();
+
+                match receiver.receive(authorization, signature, &body) {
+                    Ok(outcome) => status(outcome),
+                    Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                }
+            };
+
+            match spawner {
+                Some(spawner) => spawn_blocking(spawner, work)
+                    .await
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                None => work(),
+            }
+        },
+        Err(why) if why.is::<LengthLimitError>() => {
+            StatusCode::PAYLOAD_TOO_LARGE
+        },
+        Err(_) => StatusCode::BAD_REQUEST,
+    };
+
+    let mut response = Response::new(Full::default());
+    *response.status_mut() = status;
+
+    response
+}
+
+fn status(outcome: Outcome) -> StatusCode {
+    StatusCode::from_u16(outcome.status())
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Runs blocking work with a spawner, returning a receiver of its result
+/// which is canceled if the work was dropped without being run.
+fn spawn_blocking<T: Send + 'static>(
+    spawner: &Spawner,
+    work: impl FnOnce() -> T + Send + 'static,
+) -> oneshot::Receiver<T> {
+    let (sender, receiver) = oneshot::channel();
+
+    spawner(Box::new(move || {
+        let _ = sender.send(work());
+    }));
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use crate::executor::block_on;
+    use http::{Request, Response, StatusCode};
+    use http_body_util::Full;
+    use std::{
+        convert::Infallible,
+        future::{self, Ready},
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+        thread,
+    };
+    use super::{
+        super::{EventLog, Receiver, TEST_BODY},
+        WebhookLayer,
+        WebhookService,
+    };
+    use tower::{Layer, Service};
+
+    struct Teapot;
+
+    impl Service<Request<Full<Bytes>>> for Teapot {
+        type Response = Response<Full<Bytes>>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Request<Full<Bytes>>) -> Self::Future {
+            let mut response = Response::new(Full::default());
+            *response.status_mut() = StatusCode::IM_A_TEAPOT;
+
+            future::ready(Ok(response))
+        }
+    }
+
+    fn request(path: &str, auth: &str) -> Request<Full<Bytes>> {
+        Request::post(path)
+            .header("Authorization", auth)
+            .body(Full::new(Bytes::from_static(TEST_BODY.as_bytes())))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_service() {
+        let votes = Arc::new(Mutex::new(Vec::new()));
+        let receiver = {
+            let votes = Arc::clone(&votes);
+            Receiver::new("auth", EventLog::memory(), move |delivery| {
+                let user_id = delivery.webhook().user_id.clone();
+                votes.lock().unwrap().push(user_id);
+            })
+        };
+        let mut service = WebhookService::new(receiver);
+
+        let status = |service: &mut WebhookService<_>, auth| {
+            block_on(service.call(request("/", auth))).unwrap().status()
+        };
+
+        assert_eq!(status(&mut service, "bad"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status(&mut service, "auth"), StatusCode::OK);
+        assert_eq!(status(&mut service, "auth"), StatusCode::OK);
+        assert_eq!(*votes.lock().unwrap(), vec!["2"]);
+    }
+
+    #[test]
+    fn test_spawner() {
+        let receiver = Receiver::new("auth", EventLog::memory(), |_| {});
+        let mut service = WebhookService::new(receiver);
+        service.spawner(|job| {
+            thread::spawn(job);
+        });
+
+        let response = block_on(service.call(request("/", "auth"))).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Jobs which are never run are responded to with a server error.
+        service.spawner(drop);
+        let response = block_on(service.call(request("/", "auth"))).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_body_limit() {
+        let receiver = Receiver::new("auth", EventLog::memory(), |_| {
+            panic!("an oversized body was delivered");
+        });
+        let mut service = WebhookService::new(receiver);
+        let body = format!("{}{}", TEST_BODY, " ".repeat(64 * 1024));
+        let request = Request::post("/")
+            .header("Authorization", "auth")
+            .body(Full::new(Bytes::from(body)))
+            .unwrap();
+
+        let response = block_on(service.call(request)).unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn test_layer() {
+        let receiver = Receiver::new("auth", EventLog::memory(), |_| {});
+        let layer = WebhookLayer::new("/votes", receiver);
+        let mut service = layer.layer(Teapot);
+
+        let mut status = |path, auth| {
+            block_on(service.call(request(path, auth))).unwrap().status()
+        };
+
+        assert_eq!(status("/votes", "bad"), StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/votes", "auth"), StatusCode::OK);
+        assert_eq!(status("/other", "auth"), StatusCode::IM_A_TEAPOT);
+    }
+}
@@ -0,0 +1,276 @@
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// The name of the header containing a signed webhook's signature.
+pub const SIGNATURE_HEADER: &str = "x-topgg-signature";
+
+/// The result of verifying a webhook request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Verification {
+    /// The request's signature or authorization is invalid.
+    Invalid,
+    /// The request's legacy `Authorization` header matches the secret.
+    Legacy,
+    /// The request has neither a signature nor an authorization, or only has
+    /// an authorization while signatures are required.
+    Missing,
+    /// The request's signature is valid.
+    Signed,
+    /// The request's signature is valid, but its timestamp is outside of the
+    /// tolerance, so it may be a replay.
+    Stale,
+}
+
+impl Verification {
+    /// Whether the request was verified.
+    pub fn is_verified(self) -> bool {
+        matches!(self, Verification::Legacy | Verification::Signed)
+    }
+}
+
+/// Verifies that webhook requests were sent by the service.
+///
+/// Signed webhooks have a [`SIGNATURE_HEADER`] such as `t=...,v1=...`,
+/// where `t` is the Unix timestamp of when it was sent and `v1` is the hex
+/// encoded HMAC-SHA256 of the timestamp and body, joined by a `.`, keyed
+/// with the webhook's secret. Legacy webhooks instead have the secret as
+/// their `Authorization` header.
+///
+/// By default both schemes are accepted, so that webhooks can be migrated,
+/// and signatures must be at most 5 minutes old or ahead. Signatures and
+/// secrets are compared in constant time.
+///
+/// # Examples
+///
+/// ```rust
+/// use discord_bots_org::webhook::{Verification, Verifier};
+///
+/// let mut verifier = Verifier::new("secret");
+/// verifier.require_signature(true);
+///
+/// let result = verifier.verify(Some("secret"), None, b"{}");
+/// assert_eq!(result, Verification::Missing);
+/// ```
+///
+/// [`SIGNATURE_HEADER`]: constant.SIGNATURE_HEADER.html
+#[derive(Clone, Debug)]
+pub struct Verifier {
+    require_signature: bool,
+    secret: String,
+    tolerance: Duration,
+}
+
+impl Verifier {
+    /// Creates a new verifier with the webhook's secret.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            require_signature: false,
+            secret: secret.into(),
+            tolerance: Duration::minutes(5),
+        }
+    }
+
+    /// Sets whether requests must be signed, rejecting legacy requests.
+    ///
+    /// Defaults to false.
+    pub fn require_signature(&mut self, require: bool) -> &mut Self {
+        self.require_signature = require;
+
+        self
+    }
+
+    /// Sets how far a signature's timestamp may be from the current time.
+    ///
+    /// Defaults to 5 minutes.
+    pub fn tolerance(&mut self, tolerance: Duration) -> &mut Self {
+        self.tolerance = tolerance;
+
+        self
+    }
+
+    /// Creates the signature header value of a body sent at the given time,
+    /// such as for sending signed test webhooks.
+    pub fn sign(&self, body: &[u8], at: DateTime<Utc>) -> String {
+        let timestamp = at.timestamp().to_string();
+        let mac = self.mac(&timestamp, body).finalize().into_bytes();
+        let hex = mac.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+
+        format!("t={},v1={}", timestamp, hex)
+    }
+
+    /// Verifies a request with the given `Authorization` header, signature
+    /// header and body.
+    ///
+    /// If a signature is present it is always verified, and the
+    /// authorization is ignored.
+    pub fn verify(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> Verification {
+        self.verify_at(authorization, signature, body, Utc::now())
+    }
+
+    /// Verifies a request as if it was received at the given time.
+    ///
+    /// Refer to [`verify`] for more information.
+    ///
+    /// [`verify`]: #method.verify
+    pub fn verify_at(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> Verification {
+        if let Some(signature) = signature {
+            return self.verify_signature(signature, body, now);
+        }
+
+        match authorization {
+            Some(_) if self.require_signature => Verification::Missing,
+            Some(auth) if constant_time_eq(auth, &self.secret) => {
+                Verification::Legacy
+            },
+            Some(_) => Verification::Invalid,
+            None => Verification::Missing,
+        }
+    }
+
+    fn verify_signature(
+        &self,
+        header: &str,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> Verification {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = Some(value),
+                Some(("v1", value)) => signatures.extend(decode_hex(value)),
+                _ => {},
+            }
+        }
+
+        let (raw, timestamp) = match timestamp {
+            Some(raw) if !signatures.is_empty() => match raw.parse::<i64>() {
+                Ok(timestamp) => (raw, timestamp),
+                Err(_) => return Verification::Invalid,
+            },
+            _ => return Verification::Invalid,
+        };
+
+        let mac = self.mac(raw, body);
+        let valid = signatures
+            .iter()
+            .any(|signature| mac.clone().verify_slice(signature).is_ok());
+
+        if !valid {
+            return Verification::Invalid;
+        }
+
+        let age = now.timestamp().saturating_sub(timestamp);
+
+        if age.saturating_abs() > self.tolerance.num_seconds() {
+            Verification::Stale
+        } else {
+            Verification::Signed
+        }
+    }
+
+    fn mac(&self, timestamp: &str, body: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC accepts any key length");
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body);
+
+        mac
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+
+    a.len() == b.len()
+        && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use super::{super::TEST_BODY, Verification, Verifier};
+
+    const BODY: &[u8] = TEST_BODY.as_bytes();
+    // HMAC-SHA256 of `1546300800.{BODY}` keyed with `secret`.
+    const SIGNATURE: &str = concat!(
+        "t=1546300800,",
+        "v1=81f440b16ce1ce95a7ebb6dbaf6dc7db68cc6ab6b0875541bca91242a6f26ae8",
+    );
+
+    #[test]
+    fn test_signed() {
+        let verifier = Verifier::new("secret");
+        let now = Utc.timestamp_opt(1_546_300_860, 0).unwrap();
+        let verify = |signature: &str, body: &[u8], now| {
+            verifier.verify_at(None, Some(signature), body, now)
+        };
+
+        assert_eq!(verify(SIGNATURE, BODY, now), Verification::Signed);
+        assert_eq!(verifier.sign(BODY, now - Duration::minutes(1)), SIGNATURE);
+        assert_eq!(verify(SIGNATURE, b"{}", now), Verification::Invalid);
+        assert_eq!(
+            verify(SIGNATURE, BODY, now + Duration::minutes(5)),
+            Verification::Stale,
+        );
+        assert_eq!(
+            verify(&SIGNATURE.replace("=8", "=9"), BODY, now),
+            Verification::Invalid,
+        );
+        assert_eq!(
+            verify(&SIGNATURE.replace("t=1", "t=2"), BODY, now),
+            Verification::Invalid,
+        );
+        assert_eq!(verify("v1=zz", BODY, now), Verification::Invalid);
+        assert_eq!(
+            Verifier::new("other").verify_at(None, Some(SIGNATURE), BODY, now),
+            Verification::Invalid,
+        );
+    }
+
+    #[test]
+    fn test_legacy() {
+        let mut verifier = Verifier::new("secret");
+
+        assert_eq!(
+            verifier.verify(Some("secret"), None, BODY),
+            Verification::Legacy,
+        );
+        assert_eq!(
+            verifier.verify(Some("secre"), None, BODY),
+            Verification::Invalid,
+        );
+        assert_eq!(verifier.verify(None, None, BODY), Verification::Missing);
+
+        verifier.require_signature(true);
+        assert_eq!(
+            verifier.verify(Some("secret"), None, BODY),
+            Verification::Missing,
+        );
+    }
+}